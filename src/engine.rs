@@ -0,0 +1,301 @@
+pub mod combat;
+pub mod content;
+pub mod entity;
+pub mod fov;
+pub mod item;
+pub mod map;
+pub mod mapgen;
+pub mod object;
+pub mod path;
+pub mod save;
+pub mod schedule;
+pub mod status;
+
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+// 3party
+use rand::SeedableRng;
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+use tcod::colors::*;
+
+// own module
+use combat::Dice;
+use content::Content;
+use entity::*;
+use fov::Fov;
+use map::*;
+use mapgen::MapGenerator;
+use object::*;
+use schedule::*;
+use status::{has_status, Status};
+
+#[derive(Serialize, Deserialize)]
+pub struct Game {
+    pub map: Map,
+    pub messages: Messages,
+    pub dungeon_level: u32,
+    pub seed: u64,
+    // in ticks since the start of the run, see `Game::turn`
+    pub time: u64,
+    pub rng: GameRng,
+    pub generator: MapGenerator,
+    // derived from the map and the player position, rebuilt by `update_fov`
+    #[serde(skip)]
+    pub fov: Fov,
+    // loaded from the data files, a restored save gets it back from `load_game`
+    #[serde(skip)]
+    pub content: Rc<Content>,
+}
+
+impl Game {
+    // whole turns of a creature with normal speed since the start of the run
+    pub fn turn(&self) -> u64 {
+        self.time / TICKS_PER_TURN
+    }
+}
+
+// Every random decision of a run goes through `Game::rng`, so a seed reproduces the run.
+pub type GameRng = Pcg64;
+
+pub fn new_game(seed: u64, generator: MapGenerator, content: Rc<Content>) -> (Game, Objects) {
+    // objects and maps settings
+    let mut objects = Objects::new();
+    // the first object spawned gets the PLAYER handle
+    let player = objects.spawn(0, 0, '@', "Cuppar", WHITE, true);
+    objects.fighters.insert(
+        player,
+        Fighter {
+            base_max_hp: 30,
+            hp: 30,
+            base_defense: 2,
+            base_power: 5,
+            damage: Dice::parse("1d6+2").unwrap(),
+            xp: 0,
+            on_death: DeathCallback::Player,
+            on_hit: None,
+        },
+    );
+    objects.inventories.insert(player, vec![]);
+    objects.levels.insert(player, 1);
+    // the player moves first
+    objects.actors.insert(
+        player,
+        Actor {
+            speed: NORMAL_SPEED,
+            energy: TURN_ENERGY,
+        },
+    );
+    let mut game = Game {
+        map: vec![],
+        messages: Messages::new(),
+        dungeon_level: 1,
+        seed,
+        time: 0,
+        rng: GameRng::seed_from_u64(seed),
+        generator,
+        fov: Default::default(),
+        content,
+    };
+    make_map(&mut game, &mut objects);
+    update_fov(&mut game, &objects);
+
+    game.messages.add("Welcome!", RED);
+
+    (game, objects)
+}
+
+pub fn update_fov(game: &mut Game, objects: &Objects) {
+    let (player_x, player_y) = objects.pos(PLAYER);
+    game.fov
+        .compute(&game.map, player_x, player_y, TORCH_RADIUS);
+    for x in 0..MAP_WIDTH {
+        for y in 0..MAP_HEIGHT {
+            if player_sees(x, y, game, objects) {
+                game.map[x as usize][y as usize].explored = true;
+            }
+        }
+    }
+}
+
+// The part of the field of view the player makes out, only the tiles around it when
+// blind. Monsters still see the player over the whole field of view.
+pub fn player_sees(x: i32, y: i32, game: &Game, objects: &Objects) -> bool {
+    game.fov.is_in_fov(x, y)
+        && (!has_status(PLAYER, Status::Blind, objects) || objects.distance(PLAYER, x, y) < 2.0)
+}
+
+// The log keeps this many messages, older ones are dropped.
+pub const MESSAGE_HISTORY_SIZE: usize = 500;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Message {
+    pub text: String,
+    pub color: Color,
+    // the same message added this many times in a row
+    pub count: u32,
+}
+
+impl Message {
+    pub fn full_text(&self) -> String {
+        if self.count > 1 {
+            format!("{} (x{})", self.text, self.count)
+        } else {
+            self.text.clone()
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Messages {
+    messages: VecDeque<Message>,
+    capacity: usize,
+}
+
+impl Messages {
+    pub fn new() -> Self {
+        Self::with_capacity(MESSAGE_HISTORY_SIZE)
+    }
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            messages: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+    // the oldest messages go if there are more than fit
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.messages.len() > self.capacity {
+            self.messages.pop_front();
+        }
+    }
+    pub fn add<T>(&mut self, message: T, color: Color)
+    where
+        T: Into<String>,
+    {
+        let text = message.into();
+        if let Some(last) = self.messages.back_mut() {
+            if last.text == text && last.color == color {
+                last.count += 1;
+                return;
+            }
+        }
+        if self.messages.len() == self.capacity {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(Message {
+            text,
+            color,
+            count: 1,
+        });
+    }
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Message> + ExactSizeIterator {
+        self.messages.iter()
+    }
+}
+
+impl Default for Messages {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the walls, and where each named object stands
+    type Layout = (Vec<Vec<bool>>, Vec<(String, (i32, i32))>);
+
+    fn layout(game: &Game, objects: &Objects) -> Layout {
+        let walls = game
+            .map
+            .iter()
+            .map(|column| column.iter().map(|tile| tile.blocked).collect())
+            .collect();
+        let positions = objects
+            .positions
+            .iter()
+            .map(|(id, &pos)| (objects.name(id).to_string(), pos))
+            .collect();
+        (walls, positions)
+    }
+
+    fn texts(messages: &Messages) -> Vec<String> {
+        messages.iter().map(Message::full_text).collect()
+    }
+
+    #[test]
+    fn repeated_messages_are_counted() {
+        let mut messages = Messages::new();
+        for _ in 0..3 {
+            messages.add("You hit the orc.", WHITE);
+        }
+        messages.add("The orc is dead!", ORANGE);
+        messages.add("You hit the orc.", WHITE);
+        // the same text in another color is another message
+        messages.add("You hit the orc.", RED);
+        assert_eq!(
+            texts(&messages),
+            [
+                "You hit the orc. (x3)",
+                "The orc is dead!",
+                "You hit the orc.",
+                "You hit the orc.",
+            ]
+        );
+    }
+
+    #[test]
+    fn the_oldest_messages_make_room() {
+        let mut messages = Messages::with_capacity(3);
+        for n in 1..=5 {
+            messages.add(format!("message {}", n), WHITE);
+        }
+        assert_eq!(texts(&messages), ["message 3", "message 4", "message 5"]);
+        // repeats don't take any more room
+        messages.add("message 5", WHITE);
+        assert_eq!(messages.iter().count(), 3);
+
+        messages.set_capacity(1);
+        assert_eq!(texts(&messages), ["message 5 (x2)"]);
+        messages.set_capacity(0);
+        messages.add("message 6", WHITE);
+        assert_eq!(texts(&messages), ["message 6"]);
+    }
+
+    #[test]
+    fn a_new_game_needs_no_console() {
+        let content = Rc::new(Content::load().unwrap());
+        let (game, objects) = new_game(99, Default::default(), content);
+        let (x, y) = objects.pos(PLAYER);
+        assert!(!game.map[x as usize][y as usize].blocked);
+        assert!(game.fov.is_in_fov(x, y));
+        assert!(game.map[x as usize][y as usize].explored);
+        assert!(objects.is_alive(PLAYER));
+        assert_eq!(game.messages.iter().count(), 1);
+    }
+
+    #[test]
+    fn the_same_seed_builds_the_same_dungeon() {
+        let content = Rc::new(Content::load().unwrap());
+        for generator in [MapGenerator::Rooms, MapGenerator::Bsp, MapGenerator::Cave] {
+            let (game, objects) = new_game(1234, generator, content.clone());
+            let (again, again_objects) = new_game(1234, generator, content.clone());
+            let (other, other_objects) = new_game(4321, generator, content.clone());
+            assert_eq!(
+                layout(&game, &objects),
+                layout(&again, &again_objects),
+                "{:?}",
+                generator
+            );
+            assert_ne!(
+                layout(&game, &objects),
+                layout(&other, &other_objects),
+                "{:?}",
+                generator
+            );
+        }
+    }
+}
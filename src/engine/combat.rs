@@ -0,0 +1,183 @@
+use std::fmt;
+
+// 3party
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+// An attack hits when d20 + the attacker's power reaches this plus the target's defense.
+pub const EVASION_BASE: i32 = 10;
+pub const HIT_DIE: i32 = 20;
+// what something that cannot fight does, always 0
+pub const NO_DAMAGE: Dice = Dice {
+    count: 1,
+    sides: 1,
+    bonus: -1,
+};
+
+// Damage written like "1d6+2": roll `count` dice with `sides` sides and add `bonus`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Dice {
+    pub count: i32,
+    pub sides: i32,
+    pub bonus: i32,
+}
+
+impl Dice {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid dice \"{}\", expected e.g. \"1d6+2\"", text);
+        let (count, rest) = text.trim().split_once('d').ok_or_else(invalid)?;
+        let (sides, bonus) = match rest.find(['+', '-']) {
+            Some(sign) => (&rest[..sign], rest[sign..].trim_start_matches('+')),
+            None => (rest, "0"),
+        };
+        let dice = Dice {
+            count: count.parse().map_err(|_| invalid())?,
+            sides: sides.parse().map_err(|_| invalid())?,
+            bonus: bonus.parse().map_err(|_| invalid())?,
+        };
+        if dice.count < 1 || dice.sides < 1 {
+            return Err(invalid());
+        }
+        Ok(dice)
+    }
+
+    pub fn roll(self, rng: &mut impl Rng) -> i32 {
+        let rolled: i32 = (0..self.count).map(|_| rng.gen_range(1..=self.sides)).sum();
+        rolled + self.bonus
+    }
+}
+
+impl fmt::Display for Dice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}d{}", self.count, self.sides)?;
+        match self.bonus {
+            0 => Ok(()),
+            bonus if bonus > 0 => write!(f, "+{}", bonus),
+            bonus => write!(f, "{}", bonus),
+        }
+    }
+}
+
+impl TryFrom<String> for Dice {
+    type Error = String;
+    fn try_from(text: String) -> Result<Self, String> {
+        Dice::parse(&text)
+    }
+}
+
+impl From<Dice> for String {
+    fn from(dice: Dice) -> Self {
+        dice.to_string()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttackOutcome {
+    Miss,
+    Hit(i32),
+    // a natural 20, the damage dice are rolled twice
+    Critical(i32),
+}
+
+// Roll to hit and then for damage. A natural 1 always misses, a natural 20 always hits.
+pub fn resolve_attack(power: i32, damage: Dice, defense: i32, rng: &mut impl Rng) -> AttackOutcome {
+    let natural = rng.gen_range(1..=HIT_DIE);
+    if natural == HIT_DIE {
+        let extra: i32 = (0..damage.count)
+            .map(|_| rng.gen_range(1..=damage.sides))
+            .sum();
+        AttackOutcome::Critical((damage.roll(rng) + extra).max(0))
+    } else if natural > 1 && natural + power >= EVASION_BASE + defense {
+        AttackOutcome::Hit(damage.roll(rng).max(0))
+    } else {
+        AttackOutcome::Miss
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::engine::GameRng;
+
+    fn outcomes(power: i32, defense: i32, attacks: usize) -> Vec<AttackOutcome> {
+        let mut rng = GameRng::seed_from_u64(7);
+        let damage = Dice::parse("1d6+2").unwrap();
+        (0..attacks)
+            .map(|_| resolve_attack(power, damage, defense, &mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn dice_are_parsed_and_printed() {
+        let dice = Dice::parse("2d4-1").unwrap();
+        assert_eq!(
+            dice,
+            Dice {
+                count: 2,
+                sides: 4,
+                bonus: -1
+            }
+        );
+        assert_eq!(dice.to_string(), "2d4-1");
+        assert_eq!(Dice::parse("1d6+2").unwrap().to_string(), "1d6+2");
+        assert_eq!(Dice::parse("3d8").unwrap().bonus, 0);
+        for text in ["", "d6", "1d", "0d6", "1d0", "1x6", "1d6+", "1d6+two"] {
+            assert!(Dice::parse(text).is_err(), "{:?} parsed", text);
+        }
+    }
+
+    #[test]
+    fn rolls_stay_within_the_dice() {
+        let mut rng = GameRng::seed_from_u64(1);
+        let dice = Dice::parse("2d6+2").unwrap();
+        let rolls: Vec<i32> = (0..1000).map(|_| dice.roll(&mut rng)).collect();
+        assert!(rolls.iter().all(|roll| (4..=14).contains(roll)));
+        // both extremes come up
+        assert!(rolls.contains(&4) && rolls.contains(&14));
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_fight() {
+        assert_eq!(outcomes(3, 2, 50), outcomes(3, 2, 50));
+    }
+
+    #[test]
+    fn natural_rolls_decide_regardless_of_stats() {
+        // only a natural 1 misses a target that is far too slow
+        let easy = outcomes(100, 0, 2000);
+        let misses = easy.iter().filter(|o| **o == AttackOutcome::Miss).count();
+        assert!((60..=140).contains(&misses), "{} misses", misses);
+        // only a natural 20 hits a target that is far too quick, and always critically
+        let hard = outcomes(0, 100, 2000);
+        let hits: Vec<_> = hard.iter().filter(|o| **o != AttackOutcome::Miss).collect();
+        assert!((60..=140).contains(&hits.len()), "{} hits", hits.len());
+        assert!(hits.iter().all(|o| matches!(o, AttackOutcome::Critical(_))));
+    }
+
+    #[test]
+    fn damage_follows_the_dice() {
+        for outcome in outcomes(5, 2, 2000) {
+            match outcome {
+                AttackOutcome::Hit(damage) => assert!((3..=8).contains(&damage)),
+                AttackOutcome::Critical(damage) => assert!((4..=14).contains(&damage)),
+                AttackOutcome::Miss => {}
+            }
+        }
+    }
+
+    #[test]
+    fn defense_lowers_the_hit_chance() {
+        let hits = |defense| {
+            outcomes(3, defense, 2000)
+                .iter()
+                .filter(|o| **o != AttackOutcome::Miss)
+                .count()
+        };
+        // d20 + 3 against 10 + defense: 70% against 0, 50% against 4
+        assert!((1300..=1500).contains(&hits(0)), "{} hits", hits(0));
+        assert!((900..=1100).contains(&hits(4)), "{} hits", hits(4));
+    }
+}
@@ -0,0 +1,515 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+
+// 3party
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use tcod::colors::Color;
+
+// own modules
+use super::combat::Dice;
+use super::entity::Entity;
+use super::item::*;
+use super::map::{MAP_HEIGHT, MAP_WIDTH};
+use super::object::*;
+use super::schedule::{Actor, NORMAL_SPEED};
+use super::status::StatusEffect;
+use super::GameRng;
+
+pub const MONSTERS_FILE: &str = "data/monsters.ron";
+pub const ITEMS_FILE: &str = "data/items.ron";
+pub const LOOT_FILE: &str = "data/loot.ron";
+pub const PREFABS_FILE: &str = "data/prefabs.ron";
+
+// Everything the dungeon is populated with, loaded once at startup.
+#[derive(Debug, Default)]
+pub struct Content {
+    pub monsters: Vec<MonsterDef>,
+    pub items: Vec<ItemDef>,
+    pub loot: Vec<LootTable>,
+    pub prefabs: Vec<Prefab>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MonsterDef {
+    pub name: String,
+    pub glyph: char,
+    pub color: Color,
+    pub hp: i32,
+    pub defense: i32,
+    pub power: i32,
+    pub damage: Dice,
+    pub xp: i32,
+    pub ai: AiKind,
+    #[serde(default = "normal_speed")]
+    pub speed: i32,
+    #[serde(default)]
+    pub on_hit: Option<StatusEffect>,
+    pub spawn_weight: u32,
+    pub min_depth: u32,
+}
+
+fn normal_speed() -> i32 {
+    NORMAL_SPEED
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum AiKind {
+    Basic,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemDef {
+    pub name: String,
+    pub glyph: char,
+    pub color: Color,
+    pub effect: Item,
+    #[serde(default)]
+    pub equipment: Option<Equipment>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LootTable {
+    pub min_depth: u32,
+    pub drops: Vec<(String, u32)>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Prefab {
+    pub name: String,
+    pub min_depth: u32,
+    pub chance: f64,
+    #[serde(default)]
+    pub legend: HashMap<char, PrefabCell>,
+    pub layout: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub enum PrefabCell {
+    Wall,
+    Floor,
+    Entrance,
+    Monster(String),
+    Item(String),
+}
+
+impl Content {
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        let monsters = load_monsters(MONSTERS_FILE)?;
+        let items = load_items(ITEMS_FILE)?;
+        Ok(Content {
+            loot: load_loot(LOOT_FILE, &items)?,
+            prefabs: load_prefabs(PREFABS_FILE, &monsters, &items)?,
+            monsters,
+            items,
+        })
+    }
+
+    // Pick a monster allowed at `depth`, `None` when no definition qualifies.
+    pub fn random_monster(&self, depth: u32, rng: &mut GameRng) -> Option<&MonsterDef> {
+        let candidates: Vec<&MonsterDef> = self
+            .monsters
+            .iter()
+            .filter(|monster| monster.min_depth <= depth)
+            .collect();
+        let weights =
+            WeightedIndex::new(candidates.iter().map(|monster| monster.spawn_weight)).ok()?;
+        Some(candidates[weights.sample(rng)])
+    }
+
+    // Roll a drop from the loot table of `depth`, `None` when no table covers it.
+    pub fn random_item(&self, depth: u32, rng: &mut GameRng) -> Option<&ItemDef> {
+        let table = self
+            .loot
+            .iter()
+            .filter(|table| table.min_depth <= depth)
+            .max_by_key(|table| table.min_depth)?;
+        let weights = WeightedIndex::new(table.drops.iter().map(|&(_, weight)| weight)).ok()?;
+        self.item(&table.drops[weights.sample(rng)].0)
+    }
+
+    pub fn monster(&self, name: &str) -> Option<&MonsterDef> {
+        self.monsters.iter().find(|monster| monster.name == name)
+    }
+
+    pub fn item(&self, name: &str) -> Option<&ItemDef> {
+        self.items.iter().find(|item| item.name == name)
+    }
+}
+
+impl MonsterDef {
+    pub fn spawn(&self, x: i32, y: i32, objects: &mut Objects) -> Entity {
+        let monster = objects.spawn(x, y, self.glyph, &self.name, self.color, true);
+        objects.fighters.insert(
+            monster,
+            Fighter {
+                base_max_hp: self.hp,
+                hp: self.hp,
+                base_defense: self.defense,
+                base_power: self.power,
+                damage: self.damage,
+                xp: self.xp,
+                on_death: DeathCallback::Monster,
+                on_hit: self.on_hit,
+            },
+        );
+        objects.ais.insert(
+            monster,
+            match self.ai {
+                AiKind::Basic => Ai::Basic,
+            },
+        );
+        objects.actors.insert(monster, Actor::new(self.speed));
+        monster
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name must not be empty".into());
+        }
+        if self.hp <= 0 {
+            return Err(format!("hp must be positive, got {}", self.hp));
+        }
+        if self.defense < 0 || self.power < 0 || self.xp < 0 {
+            return Err("defense, power and xp must not be negative".into());
+        }
+        if self.min_depth == 0 {
+            return Err("min_depth starts at 1, the first dungeon level".into());
+        }
+        if self.speed <= 0 {
+            return Err(format!("speed must be positive, got {}", self.speed));
+        }
+        if let Some(effect) = self.on_hit.filter(|effect| effect.turns <= 0) {
+            return Err(format!(
+                "on_hit turns must be positive, got {}",
+                effect.turns
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl ItemDef {
+    pub fn spawn(&self, x: i32, y: i32, objects: &mut Objects) -> Entity {
+        let item = objects.spawn(x, y, self.glyph, &self.name, self.color, false);
+        objects.items.insert(item, self.effect);
+        if let Some(equipment) = self.equipment {
+            objects.equipment.insert(item, equipment);
+        }
+        item
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        use Item::*;
+        if self.name.trim().is_empty() {
+            return Err("name must not be empty".into());
+        }
+        let positive = match self.effect {
+            Heal { amount } => vec![("amount", amount)],
+            Lightning { damage, range } => vec![("damage", damage), ("range", range)],
+            Confuse { range, num_turns }
+            | Curse {
+                range, num_turns, ..
+            } => {
+                vec![("range", range), ("num_turns", num_turns)]
+            }
+            Potion { num_turns, .. } => vec![("num_turns", num_turns)],
+            Fireball { damage, radius } => vec![("damage", damage), ("radius", radius)],
+            Equipment => vec![],
+        };
+        if let Some((field, value)) = positive.into_iter().find(|&(_, value)| value <= 0) {
+            return Err(format!("{} must be positive, got {}", field, value));
+        }
+        if self.equipment.is_some_and(|equipment| equipment.equipped) {
+            return Err("items are spawned on the floor, `equipped` cannot be set".into());
+        }
+        match (self.effect, self.equipment) {
+            (Equipment, None) => Err("an Equipment item needs an `equipment` field".into()),
+            (Equipment, Some(_)) | (_, None) => Ok(()),
+            (_, Some(_)) => Err("only Equipment items can have an `equipment` field".into()),
+        }
+    }
+}
+
+impl Prefab {
+    // The layout as rows of cells, `None` for characters missing from the legend.
+    pub fn cells(&self) -> Vec<Vec<Option<PrefabCell>>> {
+        self.layout
+            .iter()
+            .map(|row| row.chars().map(|c| self.cell(c)).collect())
+            .collect()
+    }
+
+    fn cell(&self, c: char) -> Option<PrefabCell> {
+        match (self.legend.get(&c), c) {
+            (Some(cell), _) => Some(cell.clone()),
+            (None, '#') => Some(PrefabCell::Wall),
+            (None, '.') => Some(PrefabCell::Floor),
+            (None, '+') => Some(PrefabCell::Entrance),
+            (None, _) => None,
+        }
+    }
+
+    fn validate(&self, monsters: &[MonsterDef], items: &[ItemDef]) -> Result<(), String> {
+        if self.min_depth == 0 {
+            return Err("min_depth starts at 1, the first dungeon level".into());
+        }
+        if !(0.0..=1.0).contains(&self.chance) {
+            return Err(format!(
+                "chance must be between 0.0 and 1.0, got {}",
+                self.chance
+            ));
+        }
+        let width = self.layout.first().map_or(0, |row| row.chars().count());
+        if width == 0 {
+            return Err("layout must not be empty".into());
+        }
+        for (y, row) in self.layout.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(format!(
+                    "layout row {} is {} characters wide, the first row is {}",
+                    y + 1,
+                    row.chars().count(),
+                    width
+                ));
+            }
+            if let Some(c) = row.chars().find(|&c| self.cell(c).is_none()) {
+                return Err(format!(
+                    "'{}' in layout row {} is not in the legend",
+                    c,
+                    y + 1
+                ));
+            }
+        }
+        for cell in self.legend.values() {
+            match cell {
+                PrefabCell::Monster(name) if !monsters.iter().any(|m| &m.name == name) => {
+                    return Err(format!("unknown monster \"{}\" in the legend", name));
+                }
+                PrefabCell::Item(name) if !items.iter().any(|i| &i.name == name) => {
+                    return Err(format!("unknown item \"{}\" in the legend", name));
+                }
+                _ => {}
+            }
+        }
+        // any rotation has to fit inside the map border with a tile of rock around it
+        let max_size = MAP_WIDTH.min(MAP_HEIGHT) - 4;
+        if width.max(self.layout.len()) as i32 > max_size {
+            return Err(format!("layout sides must not be longer than {}", max_size));
+        }
+        let cells = self.cells();
+        let height = cells.len();
+        let mut entrances = 0;
+        for (y, row) in cells.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                if cell != &Some(PrefabCell::Entrance) {
+                    continue;
+                }
+                // tunnels are dug from the outside, an inner entrance would stay sealed
+                if x != 0 && x != width - 1 && y != 0 && y != height - 1 {
+                    return Err(format!(
+                        "the Entrance in layout row {} is not on the edge of the layout",
+                        y + 1
+                    ));
+                }
+                entrances += 1;
+            }
+        }
+        if entrances == 0 {
+            return Err("the layout needs at least one Entrance".into());
+        }
+        Ok(())
+    }
+}
+
+fn parse_file<T: DeserializeOwned>(path: &str) -> Result<T, Box<dyn Error>> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+    Ok(ron::from_str(&text).map_err(|e| format!("{}:{}", path, e))?)
+}
+
+// Names identify definitions in the other data files, so they have to be unique.
+fn check_unique_name<'a>(
+    names: &mut HashSet<&'a str>,
+    name: &'a str,
+    what: &str,
+) -> Result<(), String> {
+    if names.insert(name) {
+        Ok(())
+    } else {
+        Err(format!("the name is already used by another {}", what))
+    }
+}
+
+pub fn load_monsters(path: &str) -> Result<Vec<MonsterDef>, Box<dyn Error>> {
+    let monsters: Vec<MonsterDef> = parse_file(path)?;
+    let mut names = HashSet::new();
+    for (index, monster) in monsters.iter().enumerate() {
+        monster
+            .validate()
+            .and_then(|_| check_unique_name(&mut names, &monster.name, "monster"))
+            .map_err(|e| format!("{}: monster #{} ({}): {}", path, index + 1, monster.name, e))?;
+    }
+    if monsters.is_empty() {
+        return Err(format!("{}: defines no monsters", path).into());
+    }
+    Ok(monsters)
+}
+
+pub fn load_items(path: &str) -> Result<Vec<ItemDef>, Box<dyn Error>> {
+    let items: Vec<ItemDef> = parse_file(path)?;
+    let mut names = HashSet::new();
+    for (index, item) in items.iter().enumerate() {
+        item.validate()
+            .and_then(|_| check_unique_name(&mut names, &item.name, "item"))
+            .map_err(|e| format!("{}: item #{} ({}): {}", path, index + 1, item.name, e))?;
+    }
+    Ok(items)
+}
+
+pub fn load_loot(path: &str, items: &[ItemDef]) -> Result<Vec<LootTable>, Box<dyn Error>> {
+    let loot: Vec<LootTable> = parse_file(path)?;
+    for (index, table) in loot.iter().enumerate() {
+        let error = |e: String| format!("{}: loot table #{}: {}", path, index + 1, e);
+        if table.min_depth == 0 {
+            return Err(error("min_depth starts at 1, the first dungeon level".into()).into());
+        }
+        for (name, _) in &table.drops {
+            if !items.iter().any(|item| &item.name == name) {
+                return Err(error(format!("unknown item \"{}\" in drops", name)).into());
+            }
+        }
+    }
+    Ok(loot)
+}
+
+pub fn load_prefabs(
+    path: &str,
+    monsters: &[MonsterDef],
+    items: &[ItemDef],
+) -> Result<Vec<Prefab>, Box<dyn Error>> {
+    let prefabs: Vec<Prefab> = parse_file(path)?;
+    for (index, prefab) in prefabs.iter().enumerate() {
+        prefab
+            .validate(monsters, items)
+            .map_err(|e| format!("{}: prefab #{} ({}): {}", path, index + 1, prefab.name, e))?;
+    }
+    Ok(prefabs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn helmet(equipment: &str) -> Result<(), String> {
+        let text = format!(
+            "(name: \"helmet\", glyph: '^', color: (r: 0, g: 0, b: 0), effect: Equipment, \
+             equipment: Some({}))",
+            equipment
+        );
+        ron::from_str::<ItemDef>(&text).unwrap().validate()
+    }
+
+    const ORC: &str = "(name: \"orc\", glyph: 'o', color: (r: 63, g: 127, b: 63), hp: 10, \
+                       defense: 0, power: 3, damage: \"1d4\", xp: 35, ai: Basic, \
+                       spawn_weight: 80, min_depth: 1)";
+
+    // Load `text` from a file of its own, returning the file's path and the error. Tests
+    // run side by side, so each gives its file another name.
+    fn load_error<T: std::fmt::Debug>(
+        file: &str,
+        text: &str,
+        load: impl Fn(&str) -> Result<T, Box<dyn Error>>,
+    ) -> (String, String) {
+        let path = std::env::temp_dir().join(format!("roguelike-{}-{}", std::process::id(), file));
+        fs::write(&path, text).unwrap();
+        let path = path.to_str().unwrap().to_string();
+        let error = load(&path).unwrap_err().to_string();
+        fs::remove_file(&path).unwrap();
+        (path, error)
+    }
+
+    fn vault(layout: &[impl ToString]) -> Result<(), String> {
+        let prefab = Prefab {
+            name: "vault".into(),
+            min_depth: 1,
+            chance: 1.0,
+            legend: HashMap::new(),
+            layout: layout.iter().map(ToString::to_string).collect(),
+        };
+        prefab.validate(&[], &[])
+    }
+
+    #[test]
+    fn equipment_cannot_start_equipped() {
+        assert_eq!(helmet("(slot: Head, defense_bonus: 1)"), Ok(()));
+        assert!(helmet("(slot: Head, equipped: true)").is_err());
+    }
+
+    #[test]
+    fn prefab_entrances_are_on_the_edge() {
+        assert_eq!(vault(&["#+#", "#.#", "###"]), Ok(()));
+        assert_eq!(vault(&["###", "#.+", "###"]), Ok(()));
+        assert!(vault(&["###", "#+#", "###"]).is_err());
+        assert!(vault(&["###", "#.#", "###"]).is_err());
+    }
+
+    #[test]
+    fn prefabs_leave_room_to_be_placed() {
+        let max_size = (MAP_WIDTH.min(MAP_HEIGHT) - 4) as usize;
+        let row = |width| format!("+{}", "#".repeat(width - 1));
+        assert_eq!(vault(&vec![row(max_size); max_size]), Ok(()));
+        assert!(vault(&vec![row(max_size + 1); max_size]).is_err());
+    }
+
+    #[test]
+    fn syntax_errors_point_at_the_file_line_and_column() {
+        let text = format!("[\n{},\n{}\n]", ORC, ORC.replace("xp: 35,", "xp: 35"));
+        let (path, error) = load_error("syntax.ron", &text, load_monsters);
+        assert!(error.starts_with(&format!("{}:3:", path)), "{}", error);
+        assert!(error.ends_with("Expected comma"), "{}", error);
+
+        // values the game cannot read are pointed out the same way
+        let text = format!("[\n{},\n{}\n]", ORC, ORC.replace("1d4", "1x4"));
+        let (path, error) = load_error("dice.ron", &text, load_monsters);
+        assert!(error.starts_with(&format!("{}:3:", path)), "{}", error);
+        assert!(error.contains("invalid dice \"1x4\""), "{}", error);
+    }
+
+    #[test]
+    fn invalid_definitions_name_the_file_entry_and_field() {
+        let bat = ORC
+            .replace("\"orc\"", "\"bat\"")
+            .replace("hp: 10,", "hp: 10, speed: 0,");
+        let (path, error) =
+            load_error("invalid.ron", &format!("[{}, {}]", ORC, bat), load_monsters);
+        assert_eq!(
+            error,
+            format!("{}: monster #2 (bat): speed must be positive, got 0", path)
+        );
+
+        let text = "[(min_depth: 1, drops: [(\"sword\", 5)])]";
+        let (path, error) = load_error("loot.ron", text, |path| load_loot(path, &[]));
+        assert_eq!(
+            error,
+            format!("{}: loot table #1: unknown item \"sword\" in drops", path)
+        );
+
+        let text = "[(name: \"den\", min_depth: 1, chance: 0.5, legend: {'o': Monster(\"ogre\")}, \
+                    layout: [\"#+#\", \"#o#\", \"###\"])]";
+        let (path, error) = load_error("prefabs.ron", text, |path| load_prefabs(path, &[], &[]));
+        assert_eq!(
+            error,
+            format!(
+                "{}: prefab #1 (den): unknown monster \"ogre\" in the legend",
+                path
+            )
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use std::ops::{Index, IndexMut};
+
+// 3party
+use serde::{Deserialize, Serialize};
+
+// Stable name of an object. The slot of a destroyed entity gets a new generation when it
+// is reused, so handles to the destroyed entity never reach its successor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+// The player is the first entity of every run and is never destroyed.
+pub const PLAYER: Entity = Entity {
+    index: 0,
+    generation: 0,
+};
+
+// Hands out entity handles and remembers which of them still exist.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Entities {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    // indices of destroyed entities, reused before new ones are added
+    free: Vec<u32>,
+}
+
+impl Entities {
+    pub fn create(&mut self) -> Entity {
+        if let Some(index) = self.free.pop() {
+            let slot = index as usize;
+            self.generations[slot] += 1;
+            self.alive[slot] = true;
+            Entity {
+                index,
+                generation: self.generations[slot],
+            }
+        } else {
+            self.generations.push(0);
+            self.alive.push(true);
+            Entity {
+                index: self.generations.len() as u32 - 1,
+                generation: 0,
+            }
+        }
+    }
+
+    pub fn destroy(&mut self, id: Entity) -> bool {
+        if !self.contains(id) {
+            return false;
+        }
+        self.alive[id.index as usize] = false;
+        self.free.push(id.index);
+        true
+    }
+
+    pub fn contains(&self, id: Entity) -> bool {
+        let slot = id.index as usize;
+        self.alive.get(slot) == Some(&true) && self.generations[slot] == id.generation
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.generations
+            .iter()
+            .zip(&self.alive)
+            .enumerate()
+            .filter(|&(_, (_, &alive))| alive)
+            .map(|(index, (&generation, _))| Entity {
+                index: index as u32,
+                generation,
+            })
+    }
+}
+
+// One kind of component, stored at the index of its entity. A value is only reachable
+// through the handle it was inserted with.
+#[derive(Serialize, Deserialize)]
+pub struct Components<T> {
+    slots: Vec<Option<(u32, T)>>,
+}
+
+impl<T> Default for Components<T> {
+    fn default() -> Self {
+        Components { slots: vec![] }
+    }
+}
+
+impl<T> Components<T> {
+    pub fn insert(&mut self, id: Entity, value: T) -> Option<T> {
+        let slot = id.index as usize;
+        if self.slots.len() <= slot {
+            self.slots.resize_with(slot + 1, || None);
+        }
+        // a stale handle must not overwrite the entity that now has the slot
+        if matches!(self.slots[slot], Some((generation, _)) if generation > id.generation) {
+            return None;
+        }
+        let old = self.slots[slot].replace((id.generation, value));
+        old.filter(|&(generation, _)| generation == id.generation)
+            .map(|(_, value)| value)
+    }
+
+    pub fn remove(&mut self, id: Entity) -> Option<T> {
+        let slot = self.slots.get_mut(id.index as usize)?;
+        match slot {
+            Some((generation, _)) if *generation == id.generation => {
+                slot.take().map(|(_, value)| value)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn get(&self, id: Entity) -> Option<&T> {
+        match self.slots.get(id.index as usize)? {
+            Some((generation, value)) if *generation == id.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, id: Entity) -> Option<&mut T> {
+        match self.slots.get_mut(id.index as usize)? {
+            Some((generation, value)) if *generation == id.generation => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn contains(&self, id: Entity) -> bool {
+        self.get(id).is_some()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &T)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            slot.as_ref().map(|(generation, value)| {
+                let id = Entity {
+                    index: index as u32,
+                    generation: *generation,
+                };
+                (id, value)
+            })
+        })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Entity, &mut T)> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                slot.as_mut().map(|(generation, value)| {
+                    let id = Entity {
+                        index: index as u32,
+                        generation: *generation,
+                    };
+                    (id, value)
+                })
+            })
+    }
+
+    // Handles of every entity with this component, to loop over while changing them.
+    pub fn ids(&self) -> Vec<Entity> {
+        self.iter().map(|(id, _)| id).collect()
+    }
+}
+
+impl<T> Index<Entity> for Components<T> {
+    type Output = T;
+
+    fn index(&self, id: Entity) -> &T {
+        self.get(id).expect("entity is missing this component")
+    }
+}
+
+impl<T> IndexMut<Entity> for Components<T> {
+    fn index_mut(&mut self, id: Entity) -> &mut T {
+        self.get_mut(id).expect("entity is missing this component")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_freed_index_is_reused_with_a_new_generation() {
+        let mut entities = Entities::default();
+        let first = entities.create();
+        let second = entities.create();
+        assert_eq!(first, PLAYER);
+        assert!(entities.destroy(second));
+        assert!(!entities.destroy(second));
+        assert!(!entities.contains(second));
+
+        let third = entities.create();
+        assert_eq!(third.index, second.index);
+        assert_eq!(third.generation, second.generation + 1);
+        assert!(entities.contains(third));
+        assert!(!entities.contains(second));
+        assert_eq!(entities.iter().collect::<Vec<_>>(), [first, third]);
+        // the free list is empty again, the next one gets a fresh index
+        assert_eq!(entities.create().index, 2);
+    }
+
+    #[test]
+    fn stale_handles_reach_nothing() {
+        let mut entities = Entities::default();
+        let mut names = Components::default();
+        let old = entities.create();
+        assert_eq!(names.insert(old, "orc"), None);
+        assert_eq!(names.insert(old, "big orc"), Some("orc"));
+        entities.destroy(old);
+        let new = entities.create();
+        assert_eq!(names.get(new), None);
+        assert!(!names.contains(new));
+
+        assert_eq!(names.insert(new, "potion"), None);
+        assert_eq!(names.get(old), None);
+        assert_eq!(names.remove(old), None);
+        // writing through the old handle leaves the new entity alone
+        assert_eq!(names.insert(old, "troll"), None);
+        assert_eq!(names[new], "potion");
+        assert_eq!(names.ids(), [new]);
+
+        assert_eq!(names.remove(new), Some("potion"));
+        assert_eq!(names.get(new), None);
+    }
+}
@@ -0,0 +1,195 @@
+use super::map::*;
+
+// Multipliers (xx, xy, yx, yy) turning octant-local offsets into map offsets.
+const OCTANTS: [[i32; 4]; 8] = [
+    [1, 0, 0, 1],
+    [0, 1, 1, 0],
+    [0, -1, 1, 0],
+    [-1, 0, 0, 1],
+    [-1, 0, 0, -1],
+    [0, -1, -1, 0],
+    [0, 1, -1, 0],
+    [1, 0, 0, -1],
+];
+
+// Field of view computed with recursive shadowcasting. Walls at the edge of the view
+// are lit too, like tcod's `light_walls`.
+#[derive(Clone, Debug)]
+pub struct Fov {
+    width: i32,
+    height: i32,
+    visible: Vec<bool>,
+}
+
+impl Fov {
+    pub fn new(width: i32, height: i32) -> Self {
+        Fov {
+            width,
+            height,
+            visible: vec![false; (width * height) as usize],
+        }
+    }
+
+    pub fn is_in_fov(&self, x: i32, y: i32) -> bool {
+        self.in_bounds(x, y) && self.visible[(x + y * self.width) as usize]
+    }
+
+    pub fn compute(&mut self, map: &Map, x: i32, y: i32, radius: i32) {
+        self.visible.iter_mut().for_each(|v| *v = false);
+        if !self.in_bounds(x, y) {
+            return;
+        }
+        self.set_visible(x, y);
+        for octant in OCTANTS {
+            self.cast_light(map, (x, y), 1, 1.0, 0.0, radius, octant);
+        }
+    }
+
+    fn in_bounds(&self, x: i32, y: i32) -> bool {
+        (0..self.width).contains(&x) && (0..self.height).contains(&y)
+    }
+
+    fn set_visible(&mut self, x: i32, y: i32) {
+        self.visible[(x + y * self.width) as usize] = true;
+    }
+
+    fn blocks_sight(&self, map: &Map, x: i32, y: i32) -> bool {
+        !self.in_bounds(x, y) || map[x as usize][y as usize].block_sight
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn cast_light(
+        &mut self,
+        map: &Map,
+        (origin_x, origin_y): (i32, i32),
+        row: i32,
+        mut start: f32,
+        end: f32,
+        radius: i32,
+        [xx, xy, yx, yy]: [i32; 4],
+    ) {
+        if start < end {
+            return;
+        }
+        let mut new_start = 0.0;
+        for distance in row..=radius {
+            let dy = -distance;
+            let mut blocked = false;
+            for dx in -distance..=0 {
+                let x = origin_x + dx * xx + dy * xy;
+                let y = origin_y + dx * yx + dy * yy;
+                let left_slope = (dx as f32 - 0.5) / (dy as f32 + 0.5);
+                let right_slope = (dx as f32 + 0.5) / (dy as f32 - 0.5);
+                if start < right_slope {
+                    continue;
+                } else if end > left_slope {
+                    break;
+                }
+
+                if dx * dx + dy * dy <= radius * radius && self.in_bounds(x, y) {
+                    self.set_visible(x, y);
+                }
+                if blocked {
+                    if self.blocks_sight(map, x, y) {
+                        new_start = right_slope;
+                    } else {
+                        blocked = false;
+                        start = new_start;
+                    }
+                } else if self.blocks_sight(map, x, y) && distance < radius {
+                    blocked = true;
+                    self.cast_light(
+                        map,
+                        (origin_x, origin_y),
+                        distance + 1,
+                        start,
+                        left_slope,
+                        radius,
+                        [xx, xy, yx, yy],
+                    );
+                    new_start = right_slope;
+                }
+            }
+            if blocked {
+                break;
+            }
+        }
+    }
+}
+
+impl Default for Fov {
+    fn default() -> Self {
+        Fov::new(MAP_WIDTH, MAP_HEIGHT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: i32 = 21;
+    const CENTER: i32 = 10;
+
+    fn open_map() -> Map {
+        vec![vec![Tile::empty(); SIZE as usize]; SIZE as usize]
+    }
+
+    fn fov_from_center(map: &Map, radius: i32) -> Fov {
+        let mut fov = Fov::new(SIZE, SIZE);
+        fov.compute(map, CENTER, CENTER, radius);
+        fov
+    }
+
+    #[test]
+    fn radius_limits_an_open_view() {
+        let fov = fov_from_center(&open_map(), 5);
+        for x in 0..SIZE {
+            for y in 0..SIZE {
+                let in_radius = (x - CENTER).pow(2) + (y - CENTER).pow(2) <= 25;
+                assert_eq!(fov.is_in_fov(x, y), in_radius, "({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn walls_are_lit_but_block_sight() {
+        let mut map = open_map();
+        for y in 0..SIZE {
+            map[12][y as usize] = Tile::wall();
+        }
+        let fov = fov_from_center(&map, 8);
+        assert!(fov.is_in_fov(11, CENTER));
+        assert!(fov.is_in_fov(12, CENTER));
+        assert!(!fov.is_in_fov(13, CENTER));
+        assert!(!fov.is_in_fov(16, 12));
+        // the other side stays open
+        assert!(fov.is_in_fov(4, CENTER));
+    }
+
+    #[test]
+    fn every_octant_sees_the_same() {
+        // pillars placed symmetrically around the center
+        let mut map = open_map();
+        for (a, b) in [(2, 1), (4, 0), (3, 3)] {
+            for (x, y) in [(a, b), (b, a)] {
+                for (sx, sy) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+                    map[(CENTER + sx * x) as usize][(CENTER + sy * y) as usize] = Tile::wall();
+                }
+            }
+        }
+        let fov = fov_from_center(&map, 9);
+        let seen = |dx: i32, dy: i32| fov.is_in_fov(CENTER + dx, CENTER + dy);
+        for dx in -9..=9 {
+            for dy in -9..=9 {
+                let expected = seen(dx, dy);
+                for (x, y) in [(dx, dy), (dy, dx)] {
+                    for (sx, sy) in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+                        assert_eq!(seen(sx * x, sy * y), expected, "({}, {})", dx, dy);
+                    }
+                }
+            }
+        }
+        // and the pillars do hide something
+        assert!(!seen(8, 4));
+    }
+}
@@ -0,0 +1,415 @@
+// 3party
+use serde::{Deserialize, Serialize};
+use tcod::colors::*;
+
+// own module
+use super::combat::Dice;
+use super::entity::*;
+use super::map::*;
+use super::object::*;
+use super::status::*;
+use super::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Item {
+    Heal {
+        amount: i32,
+    },
+    Lightning {
+        damage: i32,
+        range: i32,
+    },
+    Confuse {
+        range: i32,
+        num_turns: i32,
+    },
+    // puts a status on the player
+    Potion {
+        status: Status,
+        num_turns: i32,
+    },
+    // puts a status on the targeted monster
+    Curse {
+        status: Status,
+        range: i32,
+        num_turns: i32,
+    },
+    Fireball {
+        damage: i32,
+        radius: i32,
+    },
+    Equipment,
+}
+
+// What an item needs from the player before it can be used.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Targeting {
+    None,
+    Monster { max_range: i32 },
+    Tile,
+}
+
+impl Item {
+    pub fn targeting(self) -> Targeting {
+        use Item::*;
+        match self {
+            Lightning { range, .. } | Confuse { range, .. } | Curse { range, .. } => {
+                Targeting::Monster { max_range: range }
+            }
+            Fireball { .. } => Targeting::Tile,
+            Heal { .. } | Potion { .. } | Equipment => Targeting::None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Slot {
+    Hand,
+    OffHand,
+    Head,
+    Body,
+}
+
+impl std::fmt::Display for Slot {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Slot::Hand => write!(f, "right hand"),
+            Slot::OffHand => write!(f, "left hand"),
+            Slot::Head => write!(f, "head"),
+            Slot::Body => write!(f, "body"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Equipment {
+    pub slot: Slot,
+    // bonuses left out of the item data are 0, items are never equipped on the floor
+    #[serde(default)]
+    pub equipped: bool,
+    #[serde(default)]
+    pub power_bonus: i32,
+    #[serde(default)]
+    pub defense_bonus: i32,
+    #[serde(default)]
+    pub max_hp_bonus: i32,
+    // rolled instead of the wielder's own damage
+    #[serde(default)]
+    pub damage: Option<Dice>,
+}
+
+fn get_equipped_in_slot(slot: Slot, inventory: &[Entity], objects: &Objects) -> Option<usize> {
+    inventory.iter().position(|&item| {
+        objects
+            .equipment
+            .get(item)
+            .is_some_and(|e| e.equipped && e.slot == slot)
+    })
+}
+
+// Carried items have no position, they only exist in the player's inventory.
+pub fn pick_item_up(object_id: Entity, game: &mut Game, objects: &mut Objects) {
+    if objects.inventories[PLAYER].len() >= 26 {
+        game.messages.add(
+            format!(
+                "Your inventory is full, cannot pick up {}.",
+                objects.name(object_id)
+            ),
+            RED,
+        );
+    } else if objects.positions.remove(object_id).is_some() {
+        game.messages.add(
+            format!("You picked up a {}!", objects.name(object_id)),
+            GREEN,
+        );
+        objects.inventories[PLAYER].push(object_id);
+    }
+}
+
+pub fn drop_item(inventory_id: usize, game: &mut Game, objects: &mut Objects) {
+    // still carried while it comes off, so the player's hp follows the lost bonus
+    let item = objects.inventories[PLAYER][inventory_id];
+    if objects.equipment.contains(item) {
+        dequip(item, &mut game.messages, objects);
+    }
+    objects.inventories[PLAYER].remove(inventory_id);
+    let player_pos = objects.pos(PLAYER);
+    objects.positions.insert(item, player_pos);
+    game.messages
+        .add(format!("You dropped a {}.", objects.name(item)), YELLOW);
+}
+
+pub fn use_item(
+    inventory_id: usize,
+    target: Option<(i32, i32)>,
+    game: &mut Game,
+    objects: &mut Objects,
+) -> UseResult {
+    use Item::*;
+    let item_id = objects.inventories[PLAYER][inventory_id];
+    if let Some(&item) = objects.items.get(item_id) {
+        let result = match item {
+            Heal { amount } => cast_heal(amount, game, objects),
+            Lightning { damage, range } => cast_lightning(damage, range, target, game, objects),
+            Confuse { range, num_turns } => cast_confuse(range, num_turns, target, game, objects),
+            Potion { status, num_turns } => drink_potion(status, num_turns, game, objects),
+            Curse {
+                status,
+                range,
+                num_turns,
+            } => cast_curse(status, range, num_turns, target, game, objects),
+            Fireball { damage, radius } => cast_fireball(damage, radius, target, game, objects),
+            Equipment => toggle_equipment(inventory_id, game, objects),
+        };
+        match result {
+            UseResult::UsedUp => {
+                objects.inventories[PLAYER].remove(inventory_id);
+                objects.despawn(item_id);
+            }
+            UseResult::UsedAndKept => {}
+            UseResult::Cancelled => {
+                game.messages.add("Cancelled".to_string(), WHITE);
+            }
+        }
+        result
+    } else {
+        game.messages.add(
+            format!("The {} cannot be used.", objects.name(item_id)),
+            WHITE,
+        );
+        UseResult::Cancelled
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UseResult {
+    UsedUp,
+    UsedAndKept,
+    Cancelled,
+}
+
+fn cast_heal(amount: i32, game: &mut Game, objects: &mut Objects) -> UseResult {
+    if let Some(fighter) = objects.fighters.get(PLAYER) {
+        if fighter.hp == objects.max_hp(PLAYER) {
+            game.messages
+                .add("You are alreadly at full health.".to_string(), RED);
+            return UseResult::Cancelled;
+        }
+        game.messages
+            .add("You wounds start to feel better!".to_string(), LIGHT_VIOLET);
+        objects.heal(PLAYER, amount);
+        return UseResult::UsedUp;
+    }
+    UseResult::Cancelled
+}
+fn cast_lightning(
+    damage: i32,
+    range: i32,
+    target: Option<(i32, i32)>,
+    game: &mut Game,
+    objects: &mut Objects,
+) -> UseResult {
+    let monster_id = monster_at_target(target, range, game, objects);
+    if let Some(monster_id) = monster_id {
+        game.messages.add(
+            format!(
+                "A lightning bolt strikes the {} with a loud thunder! \
+                 The damage is {} hit points.",
+                objects.name(monster_id),
+                damage
+            ),
+            LIGHT_BLUE,
+        );
+        if let Some(xp) = take_damage(monster_id, damage, game, objects) {
+            objects.gain_xp(PLAYER, xp);
+        }
+        UseResult::UsedUp
+    } else {
+        UseResult::Cancelled
+    }
+}
+
+fn cast_confuse(
+    range: i32,
+    num_turns: i32,
+    target: Option<(i32, i32)>,
+    game: &mut Game,
+    objects: &mut Objects,
+) -> UseResult {
+    let monster_id = monster_at_target(target, range, game, objects);
+    if let Some(monster_id) = monster_id {
+        set_status(
+            monster_id,
+            StatusEffect {
+                status: Status::Confused,
+                turns: num_turns,
+            },
+            objects,
+        );
+        game.messages.add(
+            format!(
+                "The eyes of {} look vacant, as he starts to stumble around!",
+                objects.name(monster_id)
+            ),
+            LIGHT_GREEN,
+        );
+        UseResult::UsedUp
+    } else {
+        UseResult::Cancelled
+    }
+}
+
+fn drink_potion(
+    status: Status,
+    num_turns: i32,
+    game: &mut Game,
+    objects: &mut Objects,
+) -> UseResult {
+    let effect = StatusEffect {
+        status,
+        turns: num_turns,
+    };
+    add_status(PLAYER, effect, game, objects);
+    UseResult::UsedUp
+}
+
+fn cast_curse(
+    status: Status,
+    range: i32,
+    num_turns: i32,
+    target: Option<(i32, i32)>,
+    game: &mut Game,
+    objects: &mut Objects,
+) -> UseResult {
+    match monster_at_target(target, range, game, objects) {
+        Some(monster_id) => {
+            let effect = StatusEffect {
+                status,
+                turns: num_turns,
+            };
+            add_status(monster_id, effect, game, objects);
+            UseResult::UsedUp
+        }
+        None => UseResult::Cancelled,
+    }
+}
+
+fn cast_fireball(
+    damage: i32,
+    radius: i32,
+    target: Option<(i32, i32)>,
+    game: &mut Game,
+    objects: &mut Objects,
+) -> UseResult {
+    let (x, y) = match target {
+        Some((x, y)) if player_sees(x, y, game, objects) => (x, y),
+        _ => return UseResult::Cancelled,
+    };
+    game.messages.add(
+        format!(
+            "The fireball explodes, burning everything within {} tiles!",
+            radius
+        ),
+        ORANGE,
+    );
+
+    let area = blast_area(x, y, radius, &game.map);
+    let mut xp_to_gain = 0;
+    for id in objects.fighters.ids() {
+        if !objects
+            .positions
+            .get(id)
+            .is_some_and(|pos| area.contains(pos))
+        {
+            continue;
+        }
+        game.messages.add(
+            format!(
+                "The {} gets burned for {} hit points.",
+                objects.name(id),
+                damage
+            ),
+            ORANGE,
+        );
+        if let Some(xp) = take_damage(id, damage, game, objects) {
+            xp_to_gain += xp;
+        }
+    }
+    objects.gain_xp(PLAYER, xp_to_gain);
+
+    UseResult::UsedUp
+}
+
+fn toggle_equipment(inventory_id: usize, game: &mut Game, objects: &mut Objects) -> UseResult {
+    let item_id = objects.inventories[PLAYER][inventory_id];
+    let equipment = match objects.equipment.get(item_id) {
+        Some(&equipment) => equipment,
+        None => return UseResult::Cancelled,
+    };
+    if equipment.equipped {
+        dequip(item_id, &mut game.messages, objects);
+    } else {
+        let inventory = &objects.inventories[PLAYER];
+        if let Some(current) = get_equipped_in_slot(equipment.slot, inventory, objects) {
+            let current_id = inventory[current];
+            dequip(current_id, &mut game.messages, objects);
+        }
+        equip(item_id, &mut game.messages, objects);
+    }
+    UseResult::UsedAndKept
+}
+
+// The monster standing on `target`, if the player can see it and it is within range.
+fn monster_at_target(
+    target: Option<(i32, i32)>,
+    max_range: i32,
+    game: &Game,
+    objects: &Objects,
+) -> Option<Entity> {
+    let (x, y) = target?;
+    if !player_sees(x, y, game, objects) || objects.distance(PLAYER, x, y) > max_range as f32 {
+        return None;
+    }
+    objects.fighter_at(x, y).filter(|&id| id != PLAYER)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn losing_a_max_hp_bonus_caps_hp() {
+        let (mut game, mut objects) = new_game(1, Default::default(), Rc::new(Default::default()));
+        let armor = objects.spawn(0, 0, ']', "armor", WHITE, false);
+        objects.items.insert(armor, Item::Equipment);
+        objects.equipment.insert(
+            armor,
+            Equipment {
+                slot: Slot::Body,
+                equipped: false,
+                power_bonus: 0,
+                defense_bonus: 0,
+                max_hp_bonus: 20,
+                damage: None,
+            },
+        );
+        pick_item_up(armor, &mut game, &mut objects);
+        for unequip in [toggle_equipment, drop_item_kept] {
+            equip(armor, &mut game.messages, &mut objects);
+            objects.fighters[PLAYER].hp = objects.max_hp(PLAYER);
+            assert_eq!(objects.fighters[PLAYER].hp, 50);
+            unequip(0, &mut game, &mut objects);
+            assert_eq!(objects.fighters[PLAYER].hp, 30);
+        }
+    }
+
+    // drop and pick up again, so the item can be equipped for the next case
+    fn drop_item_kept(inventory_id: usize, game: &mut Game, objects: &mut Objects) -> UseResult {
+        let item = objects.inventories[PLAYER][inventory_id];
+        drop_item(inventory_id, game, objects);
+        pick_item_up(item, game, objects);
+        UseResult::UsedAndKept
+    }
+}
@@ -0,0 +1,276 @@
+// 3party
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tcod::colors::*;
+
+// own modules
+use super::content::{Content, PrefabCell};
+use super::entity::*;
+use super::fov::Fov;
+use super::mapgen::prefab::stamp_prefabs;
+use super::{object::*, update_fov, Game, GameRng};
+
+pub const MAX_ROOM_MONSTERS: i32 = 3;
+
+pub const MAP_WIDTH: i32 = 160;
+pub const MAP_HEIGHT: i32 = 93;
+
+pub type Map = Vec<Vec<Tile>>;
+
+pub const MAX_ROOM_ITEMS: i32 = 2;
+
+pub const TORCH_RADIUS: i32 = 10;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct Tile {
+    pub blocked: bool,
+    pub explored: bool,
+    pub block_sight: bool,
+}
+impl Tile {
+    pub fn empty() -> Self {
+        Tile {
+            blocked: false,
+            explored: false,
+            block_sight: false,
+        }
+    }
+    pub fn wall() -> Self {
+        Tile {
+            blocked: true,
+            explored: false,
+            block_sight: true,
+        }
+    }
+}
+
+// Carve a new level with the run's generator into `game.map` and populate it.
+pub fn make_map(game: &mut Game, objects: &mut Objects) {
+    let mut dungeon = game.generator.generate(&mut game.rng);
+    let prefab_spawns = stamp_prefabs(
+        &mut dungeon.map,
+        &game.content.prefabs,
+        game.dungeon_level,
+        &mut game.rng,
+    );
+
+    let (player_x, player_y) = dungeon.player_start;
+    objects.positions[PLAYER] = (player_x, player_y);
+    // the player's region starts out empty
+    for region in dungeon.regions.iter().skip(1) {
+        place_objects(
+            region,
+            &dungeon.map,
+            objects,
+            &game.content,
+            game.dungeon_level,
+            &mut game.rng,
+        );
+    }
+
+    for ((x, y), cell) in prefab_spawns {
+        match cell {
+            PrefabCell::Monster(name) => {
+                if let Some(monster) = game.content.monster(&name) {
+                    monster.spawn(x, y, objects);
+                }
+            }
+            PrefabCell::Item(name) => {
+                if let Some(item) = game.content.item(&name) {
+                    item.spawn(x, y, objects);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let (stairs_x, stairs_y) = dungeon.stairs;
+    let stairs = objects.spawn(stairs_x, stairs_y, '>', "stairs", WHITE, false);
+    objects.renderables[stairs].always_visible = true;
+
+    game.map = dungeon.map;
+}
+
+pub fn take_stairs(game: &mut Game, objects: &mut Objects) {
+    let player_pos = objects.pos(PLAYER);
+    let player_on_stairs = objects
+        .positions
+        .iter()
+        .any(|(id, &pos)| pos == player_pos && objects.name(id) == "stairs");
+    if player_on_stairs {
+        next_level(game, objects);
+    } else {
+        game.messages.add("There are no stairs here.", WHITE);
+    }
+}
+
+pub fn next_level(game: &mut Game, objects: &mut Objects) {
+    game.messages.add(
+        "You take a moment to rest, and recover your strength.",
+        VIOLET,
+    );
+    let heal_hp = objects.max_hp(PLAYER) / 2;
+    objects.heal(PLAYER, heal_hp);
+
+    game.messages.add(
+        "After a rare moment of peace, you descend deeper into the heart of the dungeon...",
+        RED,
+    );
+    game.dungeon_level += 1;
+    // only the player and what it carries survive the trip
+    for id in objects.positions.ids() {
+        if id != PLAYER {
+            objects.despawn(id);
+        }
+    }
+    make_map(game, objects);
+    update_fov(game, objects);
+}
+
+pub fn create_room(room: Rect, map: &mut Map) {
+    for x in (room.x1 + 1)..room.x2 {
+        for y in (room.y1 + 1)..room.y2 {
+            map[x as usize][y as usize] = Tile::empty();
+        }
+    }
+}
+
+pub fn create_h_tunnel(x1: i32, x2: i32, y: i32, map: &mut Map) {
+    use std::cmp::max;
+    use std::cmp::min;
+
+    for x in min(x1, x2)..=max(x1, x2) {
+        map[x as usize][y as usize] = Tile::empty();
+    }
+}
+
+// Join two points with a horizontal and a vertical tunnel, in a random order.
+pub fn create_l_tunnel(from: (i32, i32), to: (i32, i32), map: &mut Map, rng: &mut GameRng) {
+    let ((from_x, from_y), (to_x, to_y)) = (from, to);
+    if rng.gen() {
+        create_h_tunnel(from_x, to_x, from_y, map);
+        create_v_tunnel(from_y, to_y, to_x, map);
+    } else {
+        create_v_tunnel(from_y, to_y, from_x, map);
+        create_h_tunnel(from_x, to_x, to_y, map);
+    }
+}
+
+pub fn create_v_tunnel(y1: i32, y2: i32, x: i32, map: &mut Map) {
+    use std::cmp::max;
+    use std::cmp::min;
+
+    for y in min(y1, y2)..=max(y1, y2) {
+        map[x as usize][y as usize] = Tile::empty();
+    }
+}
+#[derive(Clone, Copy, Debug)]
+pub struct Rect {
+    pub x1: i32,
+    pub y1: i32,
+    pub x2: i32,
+    pub y2: i32,
+}
+impl Rect {
+    pub fn new(x: i32, y: i32, w: i32, h: i32) -> Self {
+        Rect {
+            x1: x,
+            y1: y,
+            x2: x + w,
+            y2: y + h,
+        }
+    }
+    pub fn center(&self) -> (i32, i32) {
+        let c_x = (self.x1 + self.x2) / 2;
+        let c_y = (self.y1 + self.y2) / 2;
+        (c_x, c_y)
+    }
+    // The tiles `create_room` turns into floor.
+    pub fn floor(&self) -> Vec<(i32, i32)> {
+        let mut tiles = vec![];
+        for x in (self.x1 + 1)..self.x2 {
+            for y in (self.y1 + 1)..self.y2 {
+                tiles.push((x, y));
+            }
+        }
+        tiles
+    }
+    pub fn intersects_with(&self, other: &Rect) -> bool {
+        (self.x1 <= other.x2)
+            && (self.x2 >= other.x1)
+            && (self.y1 <= other.y2)
+            && (self.y2 >= other.y1)
+    }
+}
+pub fn place_objects(
+    region: &[(i32, i32)],
+    map: &Map,
+    objects: &mut Objects,
+    content: &Content,
+    depth: u32,
+    rng: &mut GameRng,
+) {
+    let num_monsters = rng.gen_range(0..=MAX_ROOM_MONSTERS);
+    for _ in 0..num_monsters {
+        let &(x, y) = match region.choose(rng) {
+            Some(tile) => tile,
+            None => return,
+        };
+
+        if !is_blocked(x, y, map, objects) {
+            if let Some(monster) = content.random_monster(depth, rng) {
+                monster.spawn(x, y, objects);
+            }
+        }
+    }
+    let num_items = rng.gen_range(0..=MAX_ROOM_ITEMS);
+    for _ in 0..num_items {
+        let &(x, y) = match region.choose(rng) {
+            Some(tile) => tile,
+            None => return,
+        };
+
+        if !is_blocked(x, y, map, objects) {
+            if let Some(item) = content.random_item(depth, rng) {
+                item.spawn(x, y, objects);
+            }
+        }
+    }
+}
+
+// Tiles reached by a blast of `radius` centered on (x, y). Walls stop the blast, so it
+// only spreads to tiles visible from the center.
+pub fn blast_area(x: i32, y: i32, radius: i32, map: &Map) -> Vec<(i32, i32)> {
+    let mut fov = Fov::new(MAP_WIDTH, MAP_HEIGHT);
+    fov.compute(map, x, y, radius);
+
+    let mut area = vec![];
+    for map_y in (y - radius).max(0)..=(y + radius).min(MAP_HEIGHT - 1) {
+        for map_x in (x - radius).max(0)..=(x + radius).min(MAP_WIDTH - 1) {
+            let in_radius = (map_x - x).pow(2) + (map_y - y).pow(2) <= radius.pow(2);
+            if in_radius
+                && fov.is_in_fov(map_x, map_y)
+                && !map[map_x as usize][map_y as usize].blocked
+            {
+                area.push((map_x, map_y));
+            }
+        }
+    }
+    area
+}
+
+// Diagonal steps can't squeeze between two wall corners, nor cut around a single one.
+pub fn can_step(x: i32, y: i32, dx: i32, dy: i32, map: &Map) -> bool {
+    dx == 0
+        || dy == 0
+        || (!map[(x + dx) as usize][y as usize].blocked
+            && !map[x as usize][(y + dy) as usize].blocked)
+}
+
+pub fn is_blocked(x: i32, y: i32, map: &Map, objects: &Objects) -> bool {
+    if map[x as usize][y as usize].blocked {
+        return true;
+    }
+    objects.blocker_at(x, y).is_some()
+}
@@ -0,0 +1,93 @@
+mod bsp;
+mod cave;
+pub mod prefab;
+mod rooms;
+
+use std::collections::VecDeque;
+
+// 3party
+use serde::{Deserialize, Serialize};
+
+// own modules
+use super::map::{Map, Rect, MAP_HEIGHT, MAP_WIDTH};
+use super::GameRng;
+
+// Floor tiles monsters and items of one part of a level are placed on.
+pub type Region = Vec<(i32, i32)>;
+
+// A freshly carved level, before anything is placed on it.
+pub struct Dungeon {
+    pub map: Map,
+    // the first region holds the player start and is left empty
+    pub regions: Vec<Region>,
+    pub player_start: (i32, i32),
+    pub stairs: (i32, i32),
+}
+
+impl Dungeon {
+    // Rooms cut out of solid rock, the player starts in the first one and the stairs
+    // wait in the last one.
+    pub fn from_rooms(map: Map, rooms: &[Rect]) -> Self {
+        Dungeon {
+            map,
+            regions: rooms.iter().map(Rect::floor).collect(),
+            player_start: rooms[0].center(),
+            stairs: rooms[rooms.len() - 1].center(),
+        }
+    }
+}
+
+pub type GenerateFn = fn(&mut GameRng) -> Dungeon;
+
+// The algorithm every level of a run is carved with, chosen with `--map` when it starts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum MapGenerator {
+    #[default]
+    Rooms,
+    Bsp,
+    Cave,
+}
+
+impl MapGenerator {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rooms" => Some(MapGenerator::Rooms),
+            "bsp" => Some(MapGenerator::Bsp),
+            "cave" => Some(MapGenerator::Cave),
+            _ => None,
+        }
+    }
+
+    pub fn generate(self, rng: &mut GameRng) -> Dungeon {
+        let generate: GenerateFn = match self {
+            MapGenerator::Rooms => rooms::generate,
+            MapGenerator::Bsp => bsp::generate,
+            MapGenerator::Cave => cave::generate,
+        };
+        generate(rng)
+    }
+}
+
+// Number of steps from `start` to every floor tile, `None` where it cannot be reached.
+pub fn flood_fill(map: &Map, start: (i32, i32)) -> Vec<Vec<Option<u32>>> {
+    let mut distances = vec![vec![None; MAP_HEIGHT as usize]; MAP_WIDTH as usize];
+    let mut queue = VecDeque::new();
+    distances[start.0 as usize][start.1 as usize] = Some(0);
+    queue.push_back(start);
+
+    while let Some((x, y)) = queue.pop_front() {
+        let distance = distances[x as usize][y as usize].unwrap_or(0);
+        for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 0 || ny < 0 || nx >= MAP_WIDTH || ny >= MAP_HEIGHT {
+                continue;
+            }
+            let (ux, uy) = (nx as usize, ny as usize);
+            if !map[ux][uy].blocked && distances[ux][uy].is_none() {
+                distances[ux][uy] = Some(distance + 1);
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    distances
+}
@@ -0,0 +1,75 @@
+// 3party
+use rand::Rng;
+
+// own modules
+use super::rooms::{ROOM_MAX_SIZE, ROOM_MIN_SIZE};
+use super::Dungeon;
+use crate::engine::map::*;
+use crate::engine::GameRng;
+
+// A leaf never gets smaller than this, so every leaf has room for the biggest room.
+const BSP_MIN_LEAF_SIZE: i32 = ROOM_MAX_SIZE + 2;
+
+// Binary space partition: the map is split in two until the parts get too small, every
+// leaf gets one room and every split is bridged by a tunnel between its two halves.
+pub fn generate(rng: &mut GameRng) -> Dungeon {
+    let mut map = vec![vec![Tile::wall(); MAP_HEIGHT as usize]; MAP_WIDTH as usize];
+    let mut rooms = vec![];
+    // the last row and column stay walls, like the first ones do through `create_room`
+    let area = Rect::new(0, 0, MAP_WIDTH - 1, MAP_HEIGHT - 1);
+    split(area, &mut map, &mut rooms, rng);
+
+    Dungeon::from_rooms(map, &rooms)
+}
+
+// Carve `area` and return the center of one of its rooms, for the caller to connect to.
+fn split(area: Rect, map: &mut Map, rooms: &mut Vec<Rect>, rng: &mut GameRng) -> (i32, i32) {
+    let w = area.x2 - area.x1;
+    let h = area.y2 - area.y1;
+    let can_split_x = w >= 2 * BSP_MIN_LEAF_SIZE;
+    let can_split_y = h >= 2 * BSP_MIN_LEAF_SIZE;
+    // split across the longest side, so the leaves stay close to squares
+    let split_x = match (can_split_x, can_split_y) {
+        (false, false) => return carve_leaf(area, map, rooms, rng),
+        (true, false) => true,
+        (false, true) => false,
+        (true, true) if w * 4 > h * 5 => true,
+        (true, true) if h * 4 > w * 5 => false,
+        (true, true) => rng.gen(),
+    };
+
+    let (first, second) = if split_x {
+        let at = rng.gen_range((area.x1 + BSP_MIN_LEAF_SIZE)..=(area.x2 - BSP_MIN_LEAF_SIZE));
+        (
+            Rect::new(area.x1, area.y1, at - area.x1, h),
+            Rect::new(at, area.y1, area.x2 - at, h),
+        )
+    } else {
+        let at = rng.gen_range((area.y1 + BSP_MIN_LEAF_SIZE)..=(area.y2 - BSP_MIN_LEAF_SIZE));
+        (
+            Rect::new(area.x1, area.y1, w, at - area.y1),
+            Rect::new(area.x1, at, w, area.y2 - at),
+        )
+    };
+    let first_center = split(first, map, rooms, rng);
+    let second_center = split(second, map, rooms, rng);
+    create_l_tunnel(first_center, second_center, map, rng);
+
+    if rng.gen() {
+        first_center
+    } else {
+        second_center
+    }
+}
+
+fn carve_leaf(leaf: Rect, map: &mut Map, rooms: &mut Vec<Rect>, rng: &mut GameRng) -> (i32, i32) {
+    let w = rng.gen_range(ROOM_MIN_SIZE..=ROOM_MAX_SIZE.min(leaf.x2 - leaf.x1));
+    let h = rng.gen_range(ROOM_MIN_SIZE..=ROOM_MAX_SIZE.min(leaf.y2 - leaf.y1));
+    let x = rng.gen_range(leaf.x1..=(leaf.x2 - w));
+    let y = rng.gen_range(leaf.y1..=(leaf.y2 - h));
+
+    let room = Rect::new(x, y, w, h);
+    create_room(room, map);
+    rooms.push(room);
+    room.center()
+}
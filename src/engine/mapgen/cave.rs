@@ -0,0 +1,182 @@
+// 3party
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+// own modules
+use super::{flood_fill, rooms, Dungeon, Region};
+use crate::engine::map::*;
+use crate::engine::GameRng;
+
+const CAVE_WALL_CHANCE: f64 = 0.45;
+const CAVE_SMOOTHING_STEPS: i32 = 5;
+// a tile with at least this many walls around it becomes a wall, with fewer a floor
+const CAVE_WALL_NEIGHBOURS: usize = 5;
+// reject caves whose main cavern covers less than this share of the map
+const CAVE_MIN_FLOOR_RATIO: f32 = 0.35;
+// caves that are too small this many times in a row give way to rooms
+const CAVE_MAX_TRIES: i32 = 20;
+// the cave is cut into squares of this size to spread monsters and items over it
+const CAVE_REGION_SIZE: i32 = 16;
+const CAVE_MIN_REGION_TILES: usize = 20;
+
+// Cellular automata: random noise smoothed into caverns, of which only the largest is
+// kept so the whole cave is reachable from the player start. Should no try give a big
+// enough cave, the level is carved as rooms instead.
+pub fn generate(rng: &mut GameRng) -> Dungeon {
+    for _ in 0..CAVE_MAX_TRIES {
+        let mut map = random_noise(rng);
+        for _ in 0..CAVE_SMOOTHING_STEPS {
+            map = smooth(&map);
+        }
+
+        let cavern = largest_cavern(&map, &floor_tiles(&map));
+        keep_only(&mut map, &cavern);
+        let player_start = match cavern.choose(rng) {
+            Some(&tile) => tile,
+            None => continue,
+        };
+
+        let floor = floor_tiles(&map);
+        if (floor.len() as f32) < (MAP_WIDTH * MAP_HEIGHT) as f32 * CAVE_MIN_FLOOR_RATIO {
+            continue;
+        }
+        let distances = flood_fill(&map, player_start);
+
+        // the stairs go as far from the player as the cave allows
+        let stairs = floor
+            .iter()
+            .copied()
+            .max_by_key(|&(x, y)| distances[x as usize][y as usize])
+            .unwrap_or(player_start);
+
+        return Dungeon {
+            regions: regions(&floor, player_start),
+            map,
+            player_start,
+            stairs,
+        };
+    }
+    rooms::generate(rng)
+}
+
+fn random_noise(rng: &mut GameRng) -> Map {
+    let mut map = vec![vec![Tile::wall(); MAP_HEIGHT as usize]; MAP_WIDTH as usize];
+    for x in 1..(MAP_WIDTH - 1) {
+        for y in 1..(MAP_HEIGHT - 1) {
+            if !rng.gen_bool(CAVE_WALL_CHANCE) {
+                map[x as usize][y as usize] = Tile::empty();
+            }
+        }
+    }
+    map
+}
+
+fn smooth(map: &Map) -> Map {
+    let mut smoothed = map.clone();
+    // the border stays solid so nothing walks off the map
+    for x in 1..(MAP_WIDTH - 1) {
+        for y in 1..(MAP_HEIGHT - 1) {
+            let walls = wall_neighbours(map, x, y);
+            smoothed[x as usize][y as usize] = if walls >= CAVE_WALL_NEIGHBOURS {
+                Tile::wall()
+            } else if walls < CAVE_WALL_NEIGHBOURS - 1 {
+                Tile::empty()
+            } else {
+                map[x as usize][y as usize]
+            };
+        }
+    }
+    smoothed
+}
+
+fn wall_neighbours(map: &Map, x: i32, y: i32) -> usize {
+    let mut walls = 0;
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            if (dx, dy) != (0, 0) && map[(x + dx) as usize][(y + dy) as usize].blocked {
+                walls += 1;
+            }
+        }
+    }
+    walls
+}
+
+fn floor_tiles(map: &Map) -> Vec<(i32, i32)> {
+    let mut tiles = vec![];
+    for x in 0..MAP_WIDTH {
+        for y in 0..MAP_HEIGHT {
+            if !map[x as usize][y as usize].blocked {
+                tiles.push((x, y));
+            }
+        }
+    }
+    tiles
+}
+
+// Every tile is visited once: each cavern is grown from the first floor tile no earlier
+// cavern took.
+fn largest_cavern(map: &Map, floor: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    let mut visited = vec![vec![false; MAP_HEIGHT as usize]; MAP_WIDTH as usize];
+    let mut largest = vec![];
+    for &start in floor {
+        if visited[start.0 as usize][start.1 as usize] {
+            continue;
+        }
+        visited[start.0 as usize][start.1 as usize] = true;
+        let mut cavern = vec![start];
+        let mut next = 0;
+        while let Some(&(x, y)) = cavern.get(next) {
+            next += 1;
+            for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 0 || ny < 0 || nx >= MAP_WIDTH || ny >= MAP_HEIGHT {
+                    continue;
+                }
+                let (ux, uy) = (nx as usize, ny as usize);
+                if !map[ux][uy].blocked && !visited[ux][uy] {
+                    visited[ux][uy] = true;
+                    cavern.push((nx, ny));
+                }
+            }
+        }
+        if cavern.len() > largest.len() {
+            largest = cavern;
+        }
+    }
+    largest
+}
+
+// Wall up every floor tile that is not part of `cavern`.
+fn keep_only(map: &mut Map, cavern: &[(i32, i32)]) {
+    let mut keep = vec![vec![false; MAP_HEIGHT as usize]; MAP_WIDTH as usize];
+    for &(x, y) in cavern {
+        keep[x as usize][y as usize] = true;
+    }
+    for x in 0..MAP_WIDTH as usize {
+        for y in 0..MAP_HEIGHT as usize {
+            if !keep[x][y] {
+                map[x][y] = Tile::wall();
+            }
+        }
+    }
+}
+
+// Group the floor into squares, the one with the player start first.
+fn regions(floor: &[(i32, i32)], player_start: (i32, i32)) -> Vec<Region> {
+    let columns = (MAP_WIDTH + CAVE_REGION_SIZE - 1) / CAVE_REGION_SIZE;
+    let rows = (MAP_HEIGHT + CAVE_REGION_SIZE - 1) / CAVE_REGION_SIZE;
+    let index =
+        |(x, y): (i32, i32)| (x / CAVE_REGION_SIZE + y / CAVE_REGION_SIZE * columns) as usize;
+
+    let mut regions: Vec<Region> = vec![vec![]; (columns * rows) as usize];
+    for &tile in floor {
+        regions[index(tile)].push(tile);
+    }
+    let start_region = regions.swap_remove(index(player_start));
+    let mut regions: Vec<Region> = regions
+        .into_iter()
+        .filter(|region| region.len() >= CAVE_MIN_REGION_TILES)
+        .collect();
+    regions.insert(0, start_region);
+    regions
+}
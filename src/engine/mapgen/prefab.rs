@@ -0,0 +1,178 @@
+use std::collections::VecDeque;
+
+// 3party
+use rand::Rng;
+
+// own modules
+use crate::engine::content::{Prefab, PrefabCell};
+use crate::engine::map::*;
+use crate::engine::GameRng;
+
+const PREFAB_PLACEMENT_TRIES: i32 = 50;
+
+// Stamp the prefabs allowed at `depth` into solid rock and join their entrances to the
+// level. Returns the monsters and items of the stamped prefabs with their positions.
+pub fn stamp_prefabs(
+    map: &mut Map,
+    prefabs: &[Prefab],
+    depth: u32,
+    rng: &mut GameRng,
+) -> Vec<((i32, i32), PrefabCell)> {
+    // tiles taken by a prefab, neither another prefab nor a tunnel may go through them
+    let mut occupied = vec![vec![false; MAP_HEIGHT as usize]; MAP_WIDTH as usize];
+    let mut spawns = vec![];
+
+    for prefab in prefabs.iter().filter(|prefab| prefab.min_depth <= depth) {
+        if !rng.gen_bool(prefab.chance) {
+            continue;
+        }
+        let cells = transform(prefab.cells(), rng.gen_range(0..4), rng.gen());
+        let (w, h) = (cells[0].len() as i32, cells.len() as i32);
+
+        for _ in 0..PREFAB_PLACEMENT_TRIES {
+            let x = rng.gen_range(2..(MAP_WIDTH - w - 1));
+            let y = rng.gen_range(2..(MAP_HEIGHT - h - 1));
+            if !is_free(x - 1, y - 1, w + 2, h + 2, map, &occupied) {
+                continue;
+            }
+
+            let mut entrances = vec![];
+            for (dy, row) in cells.iter().enumerate() {
+                for (dx, cell) in row.iter().enumerate() {
+                    let (cell_x, cell_y) = (x + dx as i32, y + dy as i32);
+                    occupied[cell_x as usize][cell_y as usize] = true;
+                    map[cell_x as usize][cell_y as usize] = match cell {
+                        Some(PrefabCell::Wall) | None => Tile::wall(),
+                        Some(_) => Tile::empty(),
+                    };
+                    match cell {
+                        Some(PrefabCell::Entrance) => entrances.push((cell_x, cell_y)),
+                        Some(spawn @ (PrefabCell::Monster(_) | PrefabCell::Item(_))) => {
+                            spawns.push(((cell_x, cell_y), spawn.clone()))
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            for entrance in entrances {
+                dig_to_floor(entrance, map, &occupied);
+            }
+            break;
+        }
+    }
+    spawns
+}
+
+// Turn the rows of cells clockwise `rotations` times, then mirror them left to right.
+fn transform<T: Clone>(mut cells: Vec<Vec<T>>, rotations: i32, mirror: bool) -> Vec<Vec<T>> {
+    for _ in 0..rotations {
+        let height = cells.len();
+        cells = (0..cells[0].len())
+            .map(|x| (0..height).rev().map(|y| cells[y][x].clone()).collect())
+            .collect();
+    }
+    if mirror {
+        for row in cells.iter_mut() {
+            row.reverse();
+        }
+    }
+    cells
+}
+
+// Whether the rectangle is solid rock that no prefab has claimed yet.
+fn is_free(x: i32, y: i32, w: i32, h: i32, map: &Map, occupied: &[Vec<bool>]) -> bool {
+    (x..(x + w)).all(|x| {
+        (y..(y + h))
+            .all(|y| map[x as usize][y as usize].blocked && !occupied[x as usize][y as usize])
+    })
+}
+
+// Carve the shortest tunnel from `start` to the closest floor outside of the prefabs.
+fn dig_to_floor(start: (i32, i32), map: &mut Map, occupied: &[Vec<bool>]) {
+    let mut came_from = vec![vec![None; MAP_HEIGHT as usize]; MAP_WIDTH as usize];
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+    came_from[start.0 as usize][start.1 as usize] = Some(start);
+
+    while let Some((x, y)) = queue.pop_front() {
+        if !occupied[x as usize][y as usize] && !map[x as usize][y as usize].blocked {
+            let mut tile = (x, y);
+            while tile != start {
+                map[tile.0 as usize][tile.1 as usize] = Tile::empty();
+                tile = came_from[tile.0 as usize][tile.1 as usize].unwrap_or(start);
+            }
+            return;
+        }
+        for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            let (nx, ny) = (x + dx, y + dy);
+            // the map border stays solid
+            if nx < 1 || ny < 1 || nx >= MAP_WIDTH - 1 || ny >= MAP_HEIGHT - 1 {
+                continue;
+            }
+            let (ux, uy) = (nx as usize, ny as usize);
+            if !occupied[ux][uy] && came_from[ux][uy].is_none() {
+                came_from[ux][uy] = Some((x, y));
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn prefabs_are_rotated_and_mirrored() {
+        let cells = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        assert_eq!(transform(cells.clone(), 0, false), cells);
+        assert_eq!(
+            transform(cells.clone(), 1, false),
+            vec![vec![4, 1], vec![5, 2], vec![6, 3]]
+        );
+        assert_eq!(
+            transform(cells.clone(), 2, false),
+            vec![vec![6, 5, 4], vec![3, 2, 1]]
+        );
+        assert_eq!(
+            transform(cells.clone(), 3, false),
+            vec![vec![3, 6], vec![2, 5], vec![1, 4]]
+        );
+        assert_eq!(transform(cells.clone(), 4, false), cells);
+        assert_eq!(
+            transform(cells.clone(), 0, true),
+            vec![vec![3, 2, 1], vec![6, 5, 4]]
+        );
+        assert_eq!(
+            transform(cells, 1, true),
+            vec![vec![1, 4], vec![2, 5], vec![3, 6]]
+        );
+    }
+
+    #[test]
+    fn the_largest_prefab_fits_in_any_rotation() {
+        let size = (MAP_WIDTH.min(MAP_HEIGHT) - 4) as usize;
+        let prefab = Prefab {
+            name: "hall".into(),
+            min_depth: 1,
+            chance: 1.0,
+            legend: HashMap::new(),
+            layout: vec![format!("+{}", ".".repeat(size - 1)); size],
+        };
+        for seed in 0..8 {
+            let mut map = vec![vec![Tile::wall(); MAP_HEIGHT as usize]; MAP_WIDTH as usize];
+            stamp_prefabs(
+                &mut map,
+                std::slice::from_ref(&prefab),
+                1,
+                &mut GameRng::seed_from_u64(seed),
+            );
+            let floor = map.iter().flatten().filter(|tile| !tile.blocked).count();
+            assert!(floor >= size * size, "seed {}: {} floor tiles", seed, floor);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+// 3party
+use rand::Rng;
+
+// own modules
+use super::Dungeon;
+use crate::engine::map::*;
+use crate::engine::GameRng;
+
+pub const ROOM_MAX_SIZE: i32 = 12;
+pub const ROOM_MIN_SIZE: i32 = 8;
+pub const MAX_ROOMS: i32 = 100;
+
+// Random non-overlapping rooms, each one joined to the previous one by an L-shaped tunnel.
+pub fn generate(rng: &mut GameRng) -> Dungeon {
+    let mut map = vec![vec![Tile::wall(); MAP_HEIGHT as usize]; MAP_WIDTH as usize];
+
+    let mut rooms: Vec<Rect> = vec![];
+    for _ in 0..MAX_ROOMS {
+        let w = rng.gen_range(ROOM_MIN_SIZE..=ROOM_MAX_SIZE);
+        let h = rng.gen_range(ROOM_MIN_SIZE..=ROOM_MAX_SIZE);
+        let x = rng.gen_range(0..(MAP_WIDTH - w));
+        let y = rng.gen_range(0..(MAP_HEIGHT - h));
+
+        let new_room = Rect::new(x, y, w, h);
+        let failed = rooms
+            .iter()
+            .any(|other_room| new_room.intersects_with(other_room));
+        if !failed {
+            create_room(new_room, &mut map);
+            if let Some(prev_room) = rooms.last() {
+                create_l_tunnel(prev_room.center(), new_room.center(), &mut map, rng);
+            }
+            rooms.push(new_room);
+        }
+    }
+
+    Dungeon::from_rooms(map, &rooms)
+}
@@ -0,0 +1,536 @@
+// 3party
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tcod::colors::*;
+
+// own module
+use super::combat::*;
+use super::entity::*;
+use super::item::*;
+use super::map::*;
+use super::path::*;
+use super::schedule::*;
+use super::status::*;
+use super::*;
+
+pub const LEVEL_UP_BASE: i32 = 200;
+pub const LEVEL_UP_FACTOR: i32 = 150;
+
+// Every object of the run, on the map or carried, split into one storage per component.
+// An object only has the components it needs.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Objects {
+    entities: Entities,
+    pub positions: Components<(i32, i32)>,
+    pub renderables: Components<Renderable>,
+    pub names: Components<String>,
+    // nothing else can stand on these
+    pub blockers: Components<()>,
+    pub fighters: Components<Fighter>,
+    pub ais: Components<Ai>,
+    pub items: Components<Item>,
+    pub equipment: Components<Equipment>,
+    pub inventories: Components<Vec<Entity>>,
+    // character level, only the player gains levels
+    pub levels: Components<i32>,
+    // creatures taking turns
+    pub actors: Components<Actor>,
+    pub statuses: Components<Vec<StatusEffect>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Renderable {
+    pub char: char,
+    pub color: Color,
+    // drawn on explored tiles even out of sight, like the stairs
+    pub always_visible: bool,
+}
+
+impl Objects {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    // A new object on the map with the components every map object has.
+    pub fn spawn(
+        &mut self,
+        x: i32,
+        y: i32,
+        char: char,
+        name: &str,
+        color: Color,
+        blocks: bool,
+    ) -> Entity {
+        let id = self.entities.create();
+        self.positions.insert(id, (x, y));
+        self.renderables.insert(
+            id,
+            Renderable {
+                char,
+                color,
+                always_visible: false,
+            },
+        );
+        self.names.insert(id, name.into());
+        if blocks {
+            self.blockers.insert(id, ());
+        }
+        id
+    }
+
+    pub fn despawn(&mut self, id: Entity) {
+        if !self.entities.destroy(id) {
+            return;
+        }
+        self.positions.remove(id);
+        self.renderables.remove(id);
+        self.names.remove(id);
+        self.blockers.remove(id);
+        self.fighters.remove(id);
+        self.ais.remove(id);
+        self.items.remove(id);
+        self.equipment.remove(id);
+        self.levels.remove(id);
+        self.actors.remove(id);
+        self.statuses.remove(id);
+        for item in self.inventories.remove(id).unwrap_or_default() {
+            self.despawn(item);
+        }
+    }
+
+    pub fn contains(&self, id: Entity) -> bool {
+        self.entities.contains(id)
+    }
+
+    pub fn pos(&self, id: Entity) -> (i32, i32) {
+        self.positions[id]
+    }
+    pub fn name(&self, id: Entity) -> &str {
+        self.names.get(id).map_or("something", |name| name.as_str())
+    }
+    pub fn distance(&self, id: Entity, x: i32, y: i32) -> f32 {
+        let (id_x, id_y) = self.pos(id);
+        (((x - id_x).pow(2) + (y - id_y).pow(2)) as f32).sqrt()
+    }
+    pub fn distance_between(&self, first: Entity, second: Entity) -> f32 {
+        let (x, y) = self.pos(second);
+        self.distance(first, x, y)
+    }
+    // the blocking object standing on (x, y), if any
+    pub fn blocker_at(&self, x: i32, y: i32) -> Option<Entity> {
+        self.blockers
+            .iter()
+            .map(|(id, _)| id)
+            .find(|&id| self.positions.get(id) == Some(&(x, y)))
+    }
+    pub fn fighter_at(&self, x: i32, y: i32) -> Option<Entity> {
+        self.fighters
+            .iter()
+            .map(|(id, _)| id)
+            .find(|&id| self.positions.get(id) == Some(&(x, y)))
+    }
+
+    pub fn is_alive(&self, id: Entity) -> bool {
+        self.fighters.get(id).is_some_and(|f| f.hp > 0)
+    }
+    pub fn power(&self, id: Entity) -> i32 {
+        let base_power = self.fighters.get(id).map_or(0, |f| f.base_power);
+        let bonus: i32 = self
+            .get_all_equipped(id)
+            .iter()
+            .map(|e| e.power_bonus)
+            .sum();
+        base_power + bonus
+    }
+    pub fn defense(&self, id: Entity) -> i32 {
+        let base_defense = self.fighters.get(id).map_or(0, |f| f.base_defense);
+        let bonus: i32 = self
+            .get_all_equipped(id)
+            .iter()
+            .map(|e| e.defense_bonus)
+            .sum();
+        base_defense + bonus
+    }
+    // a wielded weapon replaces the fighter's own damage
+    pub fn damage(&self, id: Entity) -> Dice {
+        let base_damage = self.fighters.get(id).map(|f| f.damage);
+        self.get_all_equipped(id)
+            .iter()
+            .find_map(|e| e.damage)
+            .or(base_damage)
+            .unwrap_or(NO_DAMAGE)
+    }
+    pub fn max_hp(&self, id: Entity) -> i32 {
+        let base_max_hp = self.fighters.get(id).map_or(0, |f| f.base_max_hp);
+        let bonus: i32 = self
+            .get_all_equipped(id)
+            .iter()
+            .map(|e| e.max_hp_bonus)
+            .sum();
+        base_max_hp + bonus
+    }
+    // equipment only counts while it is carried
+    pub fn get_all_equipped(&self, id: Entity) -> Vec<Equipment> {
+        self.inventories
+            .get(id)
+            .into_iter()
+            .flatten()
+            .filter_map(|&item| self.equipment.get(item).copied())
+            .filter(|e| e.equipped)
+            .collect()
+    }
+    pub fn gain_xp(&mut self, id: Entity, xp: i32) {
+        if let Some(fighter) = self.fighters.get_mut(id) {
+            fighter.xp += xp;
+        }
+    }
+    pub fn heal(&mut self, id: Entity, amount: i32) {
+        let max_hp = self.max_hp(id);
+        if let Some(fighter) = self.fighters.get_mut(id) {
+            fighter.hp += amount;
+            if fighter.hp > max_hp {
+                fighter.hp = max_hp;
+            }
+        }
+    }
+}
+
+pub fn player_move_or_attack(dx: i32, dy: i32, game: &mut Game, objects: &mut Objects) -> Action {
+    let (dx, dy) = if has_status(PLAYER, Status::Confused, objects) {
+        random_direction(game)
+    } else {
+        (dx, dy)
+    };
+    let (x, y) = objects.pos(PLAYER);
+    if (dx, dy) == (0, 0) || !can_step(x, y, dx, dy, &game.map) {
+        return Action::Move;
+    }
+    match objects.fighter_at(x + dx, y + dy) {
+        Some(target_id) => {
+            attack(PLAYER, target_id, game, objects);
+            Action::Attack
+        }
+        None => {
+            move_by(PLAYER, dx, dy, &game.map, objects);
+            Action::Move
+        }
+    }
+}
+
+pub fn move_by(id: Entity, dx: i32, dy: i32, map: &Map, objects: &mut Objects) {
+    let (x, y) = objects.pos(id);
+    let (new_x, new_y) = (x + dx, y + dy);
+    if !(0..MAP_WIDTH).contains(&new_x) || !(0..MAP_HEIGHT).contains(&new_y) {
+        return;
+    }
+
+    if !is_blocked(new_x, new_y, map, objects) && can_step(x, y, dx, dy, map) {
+        objects.positions[id] = (new_x, new_y);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Fighter {
+    pub base_max_hp: i32,
+    pub hp: i32,
+    pub base_defense: i32,
+    pub base_power: i32,
+    pub damage: Dice,
+    pub xp: i32,
+    pub on_death: DeathCallback,
+    // inflicted on whoever it hurts
+    pub on_hit: Option<StatusEffect>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DeathCallback {
+    Player,
+    Monster,
+}
+impl DeathCallback {
+    fn callback(self, id: Entity, game: &mut Game, objects: &mut Objects) {
+        use DeathCallback::*;
+        let callback: fn(Entity, &mut Game, &mut Objects) = match self {
+            Monster => monster_death,
+            Player => player_death,
+        };
+        callback(id, game, objects);
+    }
+}
+
+fn monster_death(monster: Entity, game: &mut Game, objects: &mut Objects) {
+    let name = objects.name(monster).to_string();
+    game.messages.add(
+        format!(
+            "{} is dead! You gain {} experience points.",
+            name,
+            objects.fighters.get(monster).map_or(0, |f| f.xp)
+        ),
+        ORANGE,
+    );
+    if let Some(renderable) = objects.renderables.get_mut(monster) {
+        renderable.char = '%';
+        renderable.color = DARK_RED;
+    }
+    objects.blockers.remove(monster);
+    objects.fighters.remove(monster);
+    objects.ais.remove(monster);
+    objects.actors.remove(monster);
+    objects.statuses.remove(monster);
+    objects
+        .names
+        .insert(monster, format!("remains of {}", name));
+}
+fn player_death(player: Entity, game: &mut Game, objects: &mut Objects) {
+    game.messages.add("You die!", ORANGE);
+    game.messages.add(
+        format!("The seed of this dungeon was {}.", game.seed),
+        LIGHT_GREY,
+    );
+    if let Some(renderable) = objects.renderables.get_mut(player) {
+        renderable.char = '%';
+        renderable.color = DARK_RED;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Ai {
+    Basic,
+}
+
+// Returns the xp the victim was worth when the damage killed it.
+pub fn take_damage(id: Entity, damage: i32, game: &mut Game, objects: &mut Objects) -> Option<i32> {
+    let fighter = objects.fighters.get_mut(id)?;
+    if damage > 0 {
+        fighter.hp -= damage;
+    }
+    let fighter = *fighter;
+    if fighter.hp <= 0 {
+        fighter.on_death.callback(id, game, objects);
+        return Some(fighter.xp);
+    }
+    None
+}
+
+pub fn attack(attacker: Entity, target: Entity, game: &mut Game, objects: &mut Objects) {
+    let outcome = resolve_attack(
+        objects.power(attacker),
+        objects.damage(attacker),
+        objects.defense(target),
+        &mut game.rng,
+    );
+    let attacker_name = objects.name(attacker).to_string();
+    let target_name = objects.name(target).to_string();
+    let damage = match outcome {
+        AttackOutcome::Miss => {
+            game.messages.add(
+                format!("{} attack {} but miss.", attacker_name, target_name),
+                LIGHT_GREY,
+            );
+            return;
+        }
+        AttackOutcome::Hit(damage) if damage > 0 => {
+            game.messages.add(
+                format!(
+                    "{} attack {} for {} hit points.",
+                    attacker_name, target_name, damage
+                ),
+                WHITE,
+            );
+            damage
+        }
+        AttackOutcome::Critical(damage) if damage > 0 => {
+            game.messages.add(
+                format!(
+                    "{} critically hit {} for {} hit points!",
+                    attacker_name, target_name, damage
+                ),
+                YELLOW,
+            );
+            damage
+        }
+        AttackOutcome::Hit(_) | AttackOutcome::Critical(_) => {
+            game.messages.add(
+                format!(
+                    "{} attack {} but it has no effect!",
+                    attacker_name, target_name
+                ),
+                WHITE,
+            );
+            return;
+        }
+    };
+    if let Some(xp) = take_damage(target, damage, game, objects) {
+        objects.gain_xp(attacker, xp);
+    } else if let Some(effect) = objects.fighters.get(attacker).and_then(|f| f.on_hit) {
+        add_status(target, effect, game, objects);
+    }
+}
+
+pub fn equip(item: Entity, messages: &mut Messages, objects: &mut Objects) {
+    let name = objects.name(item).to_string();
+    if !objects.items.contains(item) {
+        messages.add(
+            format!("Can't equip {:?} because it's not an Item.", name),
+            RED,
+        );
+        return;
+    }
+    if let Some(equipment) = objects.equipment.get_mut(item) {
+        if !equipment.equipped {
+            equipment.equipped = true;
+            messages.add(
+                format!("Equipped {} on {}.", name, equipment.slot),
+                LIGHT_GREEN,
+            );
+        }
+    } else {
+        messages.add(
+            format!("Can't equip {:?} because it's not an Equipment.", name),
+            RED,
+        );
+    }
+}
+pub fn dequip(item: Entity, messages: &mut Messages, objects: &mut Objects) {
+    let name = objects.name(item).to_string();
+    if !objects.items.contains(item) {
+        messages.add(
+            format!("Can't dequip {:?} because it's not an Item.", name),
+            RED,
+        );
+        return;
+    }
+    if let Some(equipment) = objects.equipment.get_mut(item) {
+        if equipment.equipped {
+            equipment.equipped = false;
+            messages.add(
+                format!("Dequipped {} from {}.", name, equipment.slot),
+                LIGHT_YELLOW,
+            );
+            // losing a max hp bonus must not leave the wearer above the new maximum
+            let wearer = objects
+                .inventories
+                .iter()
+                .find(|(_, items)| items.contains(&item))
+                .map(|(id, _)| id);
+            if let Some(wearer) = wearer {
+                let max_hp = objects.max_hp(wearer);
+                if let Some(fighter) = objects.fighters.get_mut(wearer) {
+                    fighter.hp = fighter.hp.min(max_hp);
+                }
+            }
+        }
+    } else {
+        messages.add(
+            format!("Can't dequip {:?} because it's not an Equipment.", name),
+            RED,
+        );
+    }
+}
+
+pub fn move_towards(id: Entity, target_x: i32, target_y: i32, map: &Map, objects: &mut Objects) {
+    let (x, y) = objects.pos(id);
+    let dx = target_x - x;
+    let dy = target_y - y;
+    let dis = ((dx.pow(2) + dy.pow(2)) as f32).sqrt();
+    let dx = (dx as f32 / dis).round() as i32;
+    let dy = (dy as f32 / dis).round() as i32;
+    move_by(id, dx, dy, map, objects);
+}
+
+// Step along the shortest way around walls and other monsters, or straight towards the
+// target when no short enough way exists.
+pub fn move_astar(id: Entity, target_x: i32, target_y: i32, map: &Map, objects: &mut Objects) {
+    let (x, y) = objects.pos(id);
+    match find_path((x, y), (target_x, target_y), map, objects, MAX_PATH_LENGTH) {
+        Some(path) => {
+            let (next_x, next_y) = path[0];
+            move_by(id, next_x - x, next_y - y, map, objects);
+        }
+        None => move_towards(id, target_x, target_y, map, objects),
+    }
+}
+
+pub fn ai_take_turn(monster_id: Entity, game: &mut Game, objects: &mut Objects) -> Action {
+    if has_status(monster_id, Status::Paralyzed, objects) {
+        return Action::Wait;
+    }
+    if has_status(monster_id, Status::Confused, objects) {
+        let (dx, dy) = random_direction(game);
+        move_by(monster_id, dx, dy, &game.map, objects);
+        return Action::Move;
+    }
+    match objects.ais.get(monster_id) {
+        Some(Ai::Basic) => ai_basic(monster_id, game, objects),
+        None => Action::Wait,
+    }
+}
+
+fn ai_basic(monster_id: Entity, game: &mut Game, objects: &mut Objects) -> Action {
+    let (monster_x, monster_y) = objects.pos(monster_id);
+    if !game.fov.is_in_fov(monster_x, monster_y) {
+        return Action::Wait;
+    }
+    let (player_x, player_y) = objects.pos(PLAYER);
+    let (dx, dy) = (player_x - monster_x, player_y - monster_y);
+    let next_to_player = objects.distance_between(monster_id, PLAYER) < 2.0;
+    // a blind monster only notices a player bumping into it
+    if has_status(monster_id, Status::Blind, objects) && !next_to_player {
+        return Action::Wait;
+    }
+    // a player diagonally behind a wall corner has to be walked around first
+    if !next_to_player || !can_step(monster_x, monster_y, dx, dy, &game.map) {
+        move_astar(monster_id, player_x, player_y, &game.map, objects);
+        Action::Move
+    } else if objects.is_alive(PLAYER) {
+        attack(monster_id, PLAYER, game, objects);
+        Action::Attack
+    } else {
+        Action::Wait
+    }
+}
+
+fn random_direction(game: &mut Game) -> (i32, i32) {
+    (game.rng.gen_range(-1..=1), game.rng.gen_range(-1..=1))
+}
+
+pub fn level_up_xp(level: i32) -> i32 {
+    LEVEL_UP_BASE + level * LEVEL_UP_FACTOR
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stat {
+    Constitution,
+    Strength,
+    Agility,
+}
+
+pub fn can_level_up(objects: &Objects) -> bool {
+    objects.fighters.get(PLAYER).map_or(0, |f| f.xp) >= level_up_xp(objects.levels[PLAYER])
+}
+
+pub fn level_up(stat: Stat, game: &mut Game, objects: &mut Objects) {
+    let level_up_xp = level_up_xp(objects.levels[PLAYER]);
+    let fighter = match objects.fighters.get_mut(PLAYER) {
+        Some(fighter) if fighter.xp >= level_up_xp => fighter,
+        _ => return,
+    };
+
+    fighter.xp -= level_up_xp;
+    match stat {
+        Stat::Constitution => {
+            fighter.base_max_hp += 20;
+            fighter.hp += 20;
+        }
+        Stat::Strength => fighter.base_power += 1,
+        Stat::Agility => fighter.base_defense += 1,
+    }
+    objects.levels[PLAYER] += 1;
+    game.messages.add(
+        format!(
+            "Your battle skills grow stronger! You reached level {}!",
+            objects.levels[PLAYER]
+        ),
+        YELLOW,
+    );
+}
@@ -0,0 +1,197 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use super::map::*;
+use super::object::Objects;
+
+// Longest path a monster will follow, anything further away falls back to direct stepping.
+pub const MAX_PATH_LENGTH: i32 = 25;
+
+const DIRECTIONS: [(i32, i32); 8] = [
+    (0, -1),
+    (0, 1),
+    (-1, 0),
+    (1, 0),
+    (-1, -1),
+    (1, -1),
+    (-1, 1),
+    (1, 1),
+];
+
+// Every step costs one turn, diagonals included, so the Chebyshev distance never
+// overestimates the remaining cost.
+fn estimate((x1, y1): (i32, i32), (x2, y2): (i32, i32)) -> i32 {
+    (x1 - x2).abs().max((y1 - y2).abs())
+}
+
+// A* search from `start` to `goal` through walkable tiles. Blocking objects are treated
+// as walls except on the goal itself, so monsters route around each other. Returns the
+// steps after `start`, ending on `goal`, or None when the goal cannot be reached within
+// `max_length` steps.
+pub fn find_path(
+    start: (i32, i32),
+    goal: (i32, i32),
+    map: &Map,
+    objects: &Objects,
+    max_length: i32,
+) -> Option<Vec<(i32, i32)>> {
+    if estimate(start, goal) > max_length {
+        return None;
+    }
+    let occupied: HashSet<(i32, i32)> = objects
+        .blockers
+        .iter()
+        .filter_map(|(id, _)| objects.positions.get(id).copied())
+        .collect();
+
+    let mut open = BinaryHeap::new();
+    let mut came_from = HashMap::new();
+    let mut cost = HashMap::new();
+    open.push(Reverse((estimate(start, goal), 0, start)));
+    cost.insert(start, 0);
+
+    while let Some(Reverse((_, steps, pos))) = open.pop() {
+        if pos == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(&previous) = came_from.get(&current) {
+                if previous == start {
+                    break;
+                }
+                path.push(previous);
+                current = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if steps > cost[&pos] {
+            // a shorter way to this tile was already expanded
+            continue;
+        }
+
+        for (dx, dy) in DIRECTIONS {
+            let next = (pos.0 + dx, pos.1 + dy);
+            let next_steps = steps + 1;
+            if !(0..MAP_WIDTH).contains(&next.0) || !(0..MAP_HEIGHT).contains(&next.1) {
+                continue;
+            }
+            if map[next.0 as usize][next.1 as usize].blocked
+                || !can_step(pos.0, pos.1, dx, dy, map)
+                || (next != goal && occupied.contains(&next))
+            {
+                continue;
+            }
+            if next_steps + estimate(next, goal) > max_length {
+                continue;
+            }
+            if cost.get(&next).is_none_or(|&known| next_steps < known) {
+                cost.insert(next, next_steps);
+                came_from.insert(next, pos);
+                open.push(Reverse((
+                    next_steps + estimate(next, goal),
+                    next_steps,
+                    next,
+                )));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use tcod::colors::WHITE;
+
+    use super::*;
+
+    fn rock() -> Map {
+        vec![vec![Tile::wall(); MAP_HEIGHT as usize]; MAP_WIDTH as usize]
+    }
+
+    fn carve(map: &mut Map, xs: std::ops::Range<i32>, ys: std::ops::Range<i32>) {
+        for x in xs {
+            for y in ys.clone() {
+                map[x as usize][y as usize] = Tile::empty();
+            }
+        }
+    }
+
+    fn assert_walkable(path: &[(i32, i32)], start: (i32, i32), map: &Map) {
+        let mut previous = start;
+        for &(x, y) in path {
+            assert!(
+                !map[x as usize][y as usize].blocked,
+                "({}, {}) is a wall",
+                x, y
+            );
+            assert_eq!(estimate(previous, (x, y)), 1, "jump to ({}, {})", x, y);
+            previous = (x, y);
+        }
+    }
+
+    #[test]
+    fn goes_around_a_wall() {
+        let mut map = rock();
+        carve(&mut map, 5..16, 5..16);
+        for tile in &mut map[10][5..13] {
+            *tile = Tile::wall();
+        }
+        let objects = Objects::new();
+        let path = find_path((8, 8), (12, 8), &map, &objects, MAX_PATH_LENGTH).unwrap();
+        assert_walkable(&path, (8, 8), &map);
+        assert_eq!(path.last(), Some(&(12, 8)));
+        assert!(path.iter().any(|&(x, y)| x == 10 && y >= 13));
+        // down to the gap and back up, at least a step per row either way
+        assert!(path.len() >= 10);
+    }
+
+    #[test]
+    fn gives_up_beyond_the_max_length() {
+        let mut map = rock();
+        carve(&mut map, 1..60, 5..6);
+        let objects = Objects::new();
+        assert_eq!(
+            find_path((2, 5), (40, 5), &map, &objects, MAX_PATH_LENGTH),
+            None
+        );
+        let path = find_path((2, 5), (40, 5), &map, &objects, 40).unwrap();
+        assert_eq!(path.len(), 38);
+    }
+
+    #[test]
+    fn blockers_are_walls_except_on_the_goal() {
+        let mut map = rock();
+        carve(&mut map, 1..20, 5..6);
+        let mut objects = Objects::new();
+        objects.spawn(6, 5, 'o', "orc", WHITE, true);
+        // an item lying in the way does not block
+        objects.spawn(4, 5, '!', "potion", WHITE, false);
+        assert_eq!(
+            find_path((2, 5), (10, 5), &map, &objects, MAX_PATH_LENGTH),
+            None
+        );
+        let path = find_path((2, 5), (6, 5), &map, &objects, MAX_PATH_LENGTH).unwrap();
+        assert_eq!(path, vec![(3, 5), (4, 5), (5, 5), (6, 5)]);
+
+        // with room to pass, the path goes around it
+        carve(&mut map, 1..20, 6..7);
+        let path = find_path((2, 5), (10, 5), &map, &objects, MAX_PATH_LENGTH).unwrap();
+        assert_walkable(&path, (2, 5), &map);
+        assert!(!path.contains(&(6, 5)));
+    }
+
+    #[test]
+    fn does_not_cut_wall_corners() {
+        let mut map = rock();
+        map[5][5] = Tile::empty();
+        map[6][6] = Tile::empty();
+        let objects = Objects::new();
+        assert_eq!(
+            find_path((5, 5), (6, 6), &map, &objects, MAX_PATH_LENGTH),
+            None
+        );
+        map[6][5] = Tile::empty();
+        let path = find_path((5, 5), (6, 6), &map, &objects, MAX_PATH_LENGTH).unwrap();
+        assert_eq!(path, vec![(6, 5), (6, 6)]);
+    }
+}
@@ -0,0 +1,60 @@
+use std::error::Error;
+use std::fs;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use super::content::Content;
+use super::object::Objects;
+use super::{update_fov, Game};
+
+pub const SAVE_FILE: &str = "savegame";
+pub const SAVE_VERSION: u32 = 13;
+
+#[derive(Serialize)]
+struct SaveRef<'a> {
+    version: u32,
+    game: &'a Game,
+    objects: &'a Objects,
+}
+
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct SaveData {
+    game: Game,
+    objects: Objects,
+}
+
+pub fn save_game(game: &Game, objects: &Objects) -> Result<(), Box<dyn Error>> {
+    let save_data = serde_json::to_string(&SaveRef {
+        version: SAVE_VERSION,
+        game,
+        objects,
+    })?;
+    fs::write(SAVE_FILE, save_data)?;
+    Ok(())
+}
+
+pub fn load_game(content: Rc<Content>) -> Result<(Game, Objects), Box<dyn Error>> {
+    let json = fs::read_to_string(SAVE_FILE)?;
+    let header = serde_json::from_str::<SaveHeader>(&json)?;
+    if header.version != SAVE_VERSION {
+        return Err(format!(
+            "save file version {} is not supported (expected {})",
+            header.version, SAVE_VERSION
+        )
+        .into());
+    }
+    let mut save_data = serde_json::from_str::<SaveData>(&json)?;
+    save_data.game.content = content;
+    update_fov(&mut save_data.game, &save_data.objects);
+    Ok((save_data.game, save_data.objects))
+}
+
+pub fn delete_save() {
+    let _ = fs::remove_file(SAVE_FILE);
+}
@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+
+use super::entity::*;
+use super::object::*;
+use super::status::*;
+use super::{update_fov, Game};
+
+// Energy a creature needs to act, and what a creature of normal speed gains in a turn.
+pub const TURN_ENERGY: i32 = 100;
+// in percent, 200 acts twice a turn and 50 every other turn
+pub const NORMAL_SPEED: i32 = 100;
+// time passes in ticks so that speeds and costs that don't divide a turn still add up
+pub const TICKS_PER_TURN: u64 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Actor {
+    pub speed: i32,
+    pub energy: i32,
+}
+
+impl Actor {
+    pub fn new(speed: i32) -> Self {
+        Actor { speed, energy: 0 }
+    }
+}
+
+// What a creature did with its turn, anything it does costs energy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Move,
+    Attack,
+    Wait,
+    UseItem,
+}
+
+impl Action {
+    pub fn cost(self) -> i32 {
+        use Action::*;
+        match self {
+            Move | Wait => TURN_ENERGY,
+            Attack => TURN_ENERGY * 6 / 5,
+            // a potion is gulped down in half the time of a step
+            UseItem => TURN_ENERGY / 2,
+        }
+    }
+}
+
+// energy gained in a turn, in percent of a creature of normal speed
+pub fn speed(id: Entity, objects: &Objects) -> i32 {
+    let mut speed = objects.actors.get(id).map_or(0, |actor| actor.speed);
+    if has_status(id, Status::Hasted, objects) {
+        speed *= 2;
+    }
+    if has_status(id, Status::Slowed, objects) {
+        speed /= 2;
+    }
+    speed
+}
+
+// Energy gained in the tick that ends at `time`. The speed is handed out over the ticks
+// of a turn so that a turn adds up to all of it, however slow or odd the speed.
+fn energy_gain(speed: i32, time: u64) -> i32 {
+    let scale = (NORMAL_SPEED as i64) * (TICKS_PER_TURN as i64);
+    let gained_by = |time: u64| speed as i64 * TURN_ENERGY as i64 * time as i64 / scale;
+    (gained_by(time) - gained_by(time - 1)) as i32
+}
+
+pub fn can_act(id: Entity, objects: &Objects) -> bool {
+    objects
+        .actors
+        .get(id)
+        .is_some_and(|actor| actor.energy >= TURN_ENERGY)
+}
+
+pub fn spend_energy(id: Entity, action: Action, objects: &mut Objects) {
+    if let Some(actor) = objects.actors.get_mut(id) {
+        actor.energy -= action.cost();
+    }
+}
+
+// Pay for the player's action, then let time pass with the monsters acting as their
+// energy allows until the player can act again. A paralyzed player waits it out.
+pub fn run_monster_turns(action: Action, game: &mut Game, objects: &mut Objects) {
+    spend_energy(PLAYER, action, objects);
+    update_fov(game, objects);
+    while objects.is_alive(PLAYER) {
+        if can_act(PLAYER, objects) {
+            if !has_status(PLAYER, Status::Paralyzed, objects) {
+                break;
+            }
+            spend_energy(PLAYER, Action::Wait, objects);
+        }
+        game.time += 1;
+        if game.time.is_multiple_of(TICKS_PER_TURN) {
+            tick_statuses(game, objects);
+        }
+        for id in objects.actors.ids() {
+            objects.actors[id].energy += energy_gain(speed(id, objects), game.time);
+        }
+        for id in objects.ais.ids() {
+            // the monster may have died already
+            while objects.ais.contains(id) && objects.is_alive(PLAYER) && can_act(id, objects) {
+                let action = ai_take_turn(id, game, objects);
+                spend_energy(id, action, objects);
+            }
+        }
+    }
+    // a status running out may have changed what the player sees
+    update_fov(game, objects);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use tcod::colors::WHITE;
+
+    use super::*;
+    use crate::engine::map::*;
+    use crate::engine::new_game;
+
+    // the player in the middle of an open floor, with monsters of the given speeds four
+    // tiles away, walking towards it one step per action
+    fn open_floor(speeds: &[i32]) -> (Game, Objects, Vec<Entity>) {
+        let (mut game, mut objects) = new_game(1, Default::default(), Rc::new(Default::default()));
+        game.map = vec![vec![Tile::empty(); MAP_HEIGHT as usize]; MAP_WIDTH as usize];
+        objects.positions[PLAYER] = (20, 20);
+        let monsters = speeds
+            .iter()
+            .enumerate()
+            .map(|(n, &speed)| {
+                let monster = objects.spawn(24, 18 + 2 * n as i32, 'm', "monster", WHITE, true);
+                objects.ais.insert(monster, Ai::Basic);
+                objects.actors.insert(monster, Actor::new(speed));
+                monster
+            })
+            .collect();
+        update_fov(&mut game, &objects);
+        (game, objects, monsters)
+    }
+
+    fn steps_taken(monster: Entity, objects: &Objects) -> i32 {
+        24 - objects.pos(monster).0
+    }
+
+    #[test]
+    fn every_speed_adds_up_over_a_turn() {
+        for speed in [1, 7, 15, 50, 100, 125, 200, 333] {
+            let gained: i32 = (1..=TICKS_PER_TURN)
+                .map(|time| energy_gain(speed, 3 * TICKS_PER_TURN + time))
+                .sum();
+            assert_eq!(gained, speed);
+        }
+    }
+
+    #[test]
+    fn speed_decides_how_often_a_creature_acts() {
+        let (mut game, mut objects, monsters) = open_floor(&[200, 50]);
+        let (bat, zombie) = (monsters[0], monsters[1]);
+        run_monster_turns(Action::Wait, &mut game, &mut objects);
+        assert_eq!(game.time, TICKS_PER_TURN);
+        assert_eq!(steps_taken(bat, &objects), 2);
+        assert_eq!(steps_taken(zombie, &objects), 0);
+        run_monster_turns(Action::Wait, &mut game, &mut objects);
+        assert_eq!(steps_taken(bat, &objects), 3);
+        assert_eq!(steps_taken(zombie, &objects), 1);
+    }
+
+    #[test]
+    fn a_hasted_player_acts_twice_a_turn() {
+        let (mut game, mut objects, monsters) = open_floor(&[100]);
+        set_status(
+            PLAYER,
+            StatusEffect {
+                status: Status::Hasted,
+                turns: 10,
+            },
+            &mut objects,
+        );
+        run_monster_turns(Action::Wait, &mut game, &mut objects);
+        assert_eq!(game.time, TICKS_PER_TURN / 2);
+        assert_eq!(steps_taken(monsters[0], &objects), 0);
+        run_monster_turns(Action::Wait, &mut game, &mut objects);
+        assert_eq!(game.time, TICKS_PER_TURN);
+        assert_eq!(steps_taken(monsters[0], &objects), 1);
+    }
+}
@@ -0,0 +1,202 @@
+// 3party
+use serde::{Deserialize, Serialize};
+use tcod::colors::*;
+
+// own module
+use super::entity::*;
+use super::object::*;
+use super::Game;
+
+pub const POISON_DAMAGE: i32 = 1;
+pub const REGENERATION_AMOUNT: i32 = 1;
+
+// Lasting conditions of a creature, any number of different ones at a time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Status {
+    // moves in random directions
+    Confused,
+    // loses hit points every turn
+    Poisoned,
+    // gains hit points every turn
+    Regenerating,
+    // acts twice as often
+    Hasted,
+    // acts half as often
+    Slowed,
+    // only makes out what is right next to it
+    Blind,
+    // cannot act at all
+    Paralyzed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatusEffect {
+    pub status: Status,
+    // turns left, counted in turns of normal speed so haste doesn't shorten it
+    pub turns: i32,
+}
+
+impl Status {
+    pub fn name(self) -> &'static str {
+        use Status::*;
+        match self {
+            Confused => "confused",
+            Poisoned => "poisoned",
+            Regenerating => "regenerating",
+            Hasted => "hasted",
+            Slowed => "slowed",
+            Blind => "blind",
+            Paralyzed => "paralyzed",
+        }
+    }
+
+    // short enough to list several in the panel
+    pub fn label(self) -> &'static str {
+        use Status::*;
+        match self {
+            Confused => "Conf",
+            Poisoned => "Pois",
+            Regenerating => "Regen",
+            Hasted => "Haste",
+            Slowed => "Slow",
+            Blind => "Blind",
+            Paralyzed => "Para",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        use Status::*;
+        match self {
+            Confused => LIGHT_GREEN,
+            Poisoned => GREEN,
+            Regenerating => LIGHT_VIOLET,
+            Hasted => LIGHT_BLUE,
+            Slowed => LIGHT_GREY,
+            Blind => DARK_GREY,
+            Paralyzed => LIGHT_YELLOW,
+        }
+    }
+
+    // runs once a turn while the status lasts
+    fn tick(self, id: Entity, game: &mut Game, objects: &mut Objects) {
+        match self {
+            // only the player poisons monsters, so the kill is the player's
+            Status::Poisoned => {
+                if let Some(xp) = take_damage(id, POISON_DAMAGE, game, objects) {
+                    if id != PLAYER {
+                        objects.gain_xp(PLAYER, xp);
+                    }
+                }
+            }
+            Status::Regenerating => objects.heal(id, REGENERATION_AMOUNT),
+            _ => {}
+        }
+    }
+}
+
+pub fn has_status(id: Entity, status: Status, objects: &Objects) -> bool {
+    objects
+        .statuses
+        .get(id)
+        .is_some_and(|effects| effects.iter().any(|effect| effect.status == status))
+}
+
+// Add the effect without telling the player. The same status again lasts as long as
+// the longer of the two.
+pub fn set_status(id: Entity, effect: StatusEffect, objects: &mut Objects) {
+    if !objects.statuses.contains(id) {
+        objects.statuses.insert(id, vec![]);
+    }
+    let effects = &mut objects.statuses[id];
+    match effects.iter_mut().find(|e| e.status == effect.status) {
+        Some(current) => current.turns = current.turns.max(effect.turns),
+        None => effects.push(effect),
+    }
+}
+
+pub fn add_status(id: Entity, effect: StatusEffect, game: &mut Game, objects: &mut Objects) {
+    let text = if id == PLAYER {
+        format!("You are {}!", effect.status.name())
+    } else {
+        format!("The {} is {}!", objects.name(id), effect.status.name())
+    };
+    game.messages.add(text, effect.status.color());
+    set_status(id, effect, objects);
+}
+
+// A turn has passed: run the tick of every status, then count it down.
+pub fn tick_statuses(game: &mut Game, objects: &mut Objects) {
+    for id in objects.statuses.ids() {
+        let effects = objects.statuses[id].clone();
+        for effect in &effects {
+            effect.status.tick(id, game, objects);
+        }
+        // poison may have killed it
+        if !objects.is_alive(id) {
+            objects.statuses.remove(id);
+            continue;
+        }
+
+        let mut remaining = vec![];
+        for mut effect in effects {
+            effect.turns -= 1;
+            if effect.turns > 0 {
+                remaining.push(effect);
+                continue;
+            }
+            let text = if id == PLAYER {
+                format!("You are no longer {}.", effect.status.name())
+            } else {
+                format!(
+                    "The {} is no longer {}!",
+                    objects.name(id),
+                    effect.status.name()
+                )
+            };
+            game.messages.add(text, effect.status.color());
+        }
+        if remaining.is_empty() {
+            objects.statuses.remove(id);
+        } else {
+            objects.statuses[id] = remaining;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::engine::combat::NO_DAMAGE;
+    use crate::engine::new_game;
+
+    #[test]
+    fn a_poisoned_monster_is_worth_its_xp() {
+        let (mut game, mut objects) = new_game(1, Default::default(), Rc::new(Default::default()));
+        let orc = objects.spawn(1, 1, 'o', "orc", GREEN, true);
+        objects.fighters.insert(
+            orc,
+            Fighter {
+                base_max_hp: 10,
+                hp: POISON_DAMAGE,
+                base_defense: 0,
+                base_power: 0,
+                damage: NO_DAMAGE,
+                xp: 35,
+                on_death: DeathCallback::Monster,
+                on_hit: None,
+            },
+        );
+        let effect = StatusEffect {
+            status: Status::Poisoned,
+            turns: 5,
+        };
+        add_status(orc, effect, &mut game, &mut objects);
+        tick_statuses(&mut game, &mut objects);
+        assert!(!objects.is_alive(orc));
+        assert!(!objects.statuses.contains(orc));
+        assert_eq!(objects.fighters[PLAYER].xp, 35);
+    }
+}
@@ -0,0 +1,12 @@
+pub mod history;
+pub mod input;
+pub mod keymap;
+pub mod render;
+pub mod renderer;
+pub mod replay;
+pub mod ui;
+
+use crate::engine::map::{MAP_HEIGHT, MAP_WIDTH};
+
+pub const SCREEN_WIDTH: i32 = MAP_WIDTH;
+pub const SCREEN_HEIGHT: i32 = MAP_HEIGHT + render::PANEL_HEIGHT;
@@ -0,0 +1,150 @@
+use tcod::colors::{DARKER_YELLOW, LIGHT_GREY, LIGHT_YELLOW, WHITE};
+
+use crate::engine::Messages;
+
+use super::input::KeyName;
+use super::render::Frontend;
+
+// Full-screen view of the message history. The arrows scroll by a line, PageUp and
+// PageDown by a page, '/' searches and 'n' / 'N' jump to the next older / newer match.
+pub fn message_log(frontend: &mut Frontend, messages: &Messages) {
+    let lines: Vec<_> = messages
+        .iter()
+        .map(|message| (message.full_text(), message.color))
+        .collect();
+    // one past the newest line on screen, the log opens on the latest messages
+    let mut bottom = lines.len();
+    let mut query = String::new();
+    let mut typing = false;
+    let mut status = String::new();
+
+    loop {
+        // rows left for messages below the title and above the status line
+        let page = (frontend.screen.size().1 - 2).max(1) as usize;
+        // never scroll past either end of the log
+        bottom = bottom.clamp(page.min(lines.len()), lines.len());
+        draw_log(frontend, &lines, bottom, page, &query, typing, &status);
+        let key = frontend.screen.wait_for_key();
+        if frontend.screen.window_closed() {
+            return;
+        }
+
+        if typing {
+            match key.name {
+                KeyName::Text(c) => query.push(c),
+                KeyName::Backspace => {
+                    query.pop();
+                }
+                KeyName::Escape => {
+                    query.clear();
+                    typing = false;
+                }
+                KeyName::Enter => {
+                    typing = false;
+                    // start from the newest line so the latest match comes first
+                    status = search(&lines, &query, lines.len(), true, &mut bottom);
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        status.clear();
+        match key.name {
+            KeyName::Escape => return,
+            KeyName::Up => bottom = bottom.saturating_sub(1),
+            KeyName::Down => bottom += 1,
+            KeyName::PageUp => bottom = bottom.saturating_sub(page),
+            KeyName::PageDown => bottom += page,
+            KeyName::Text('/') => {
+                query.clear();
+                typing = true;
+            }
+            KeyName::Text('n') if !query.is_empty() => {
+                status = search(&lines, &query, bottom.saturating_sub(1), true, &mut bottom);
+            }
+            KeyName::Text('N') if !query.is_empty() => {
+                status = search(&lines, &query, bottom, false, &mut bottom);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn matches(line: &str, query: &str) -> bool {
+    !query.is_empty() && line.to_lowercase().contains(&query.to_lowercase())
+}
+
+// Move `bottom` onto the first match before `from` (older) or from `from` on (newer).
+fn search(
+    lines: &[(String, tcod::Color)],
+    query: &str,
+    from: usize,
+    older: bool,
+    bottom: &mut usize,
+) -> String {
+    let found = if older {
+        lines[..from.min(lines.len())]
+            .iter()
+            .rposition(|(line, _)| matches(line, query))
+    } else {
+        lines
+            .iter()
+            .skip(from)
+            .position(|(line, _)| matches(line, query))
+            .map(|index| index + from)
+    };
+    match found {
+        Some(index) => {
+            *bottom = index + 1;
+            String::new()
+        }
+        None => format!("No more messages matching \"{}\".", query),
+    }
+}
+
+fn draw_log(
+    frontend: &mut Frontend,
+    lines: &[(String, tcod::Color)],
+    bottom: usize,
+    page: usize,
+    query: &str,
+    typing: bool,
+    status: &str,
+) {
+    let screen = frontend.screen.as_mut();
+    let (width, height) = screen.size();
+    screen.clear();
+
+    let title = if typing {
+        format!("Search: {}_", query)
+    } else {
+        "Message log. Up/Down, PageUp/PageDown: scroll, /: search, n/N: older/newer match, \
+         Esc: close"
+            .to_string()
+    };
+    screen.print(0, 0, &title, LIGHT_YELLOW);
+
+    let top = bottom.saturating_sub(page);
+    for (row, (line, color)) in lines[top..bottom].iter().enumerate() {
+        let y = row as i32 + 1;
+        if matches(line, query) && !typing {
+            for x in 0..width {
+                screen.set_background(x, y, DARKER_YELLOW);
+            }
+        }
+        screen.print(0, y, line, *color);
+    }
+
+    let position = if lines.is_empty() {
+        "No messages yet.".to_string()
+    } else {
+        format!("Messages {}-{} of {}", top + 1, bottom, lines.len())
+    };
+    if status.is_empty() {
+        screen.print(0, height - 1, &position, LIGHT_GREY);
+    } else {
+        screen.print(0, height - 1, status, WHITE);
+    }
+    screen.flush();
+}
@@ -0,0 +1,346 @@
+use tcod::colors::{LIGHT_CYAN, LIGHT_GREEN, RED};
+
+use crate::engine::entity::*;
+use crate::engine::item::*;
+use crate::engine::map::{take_stairs, MAP_HEIGHT, MAP_WIDTH};
+use crate::engine::object::*;
+use crate::engine::schedule::Action;
+use crate::engine::{player_sees, Game};
+
+use super::history::message_log;
+use super::keymap::Command;
+use super::render::*;
+use super::renderer::InputEvent;
+use super::replay::{next_input, RecordedInput};
+use super::ui::{help_screen, inventory_menu};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PlayerAction {
+    Replay,
+    TookTurn(Action),
+    DidntTakeTurn,
+    Exit,
+}
+
+// A key press independent of the console backend, matched against the keymap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyPress {
+    pub name: KeyName,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyName {
+    // a printable key, as reported before any modifier is applied
+    Char(char),
+    // the character a key combination produced, e.g. '>' for shift + '.'
+    Text(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Escape,
+    Backspace,
+    PageUp,
+    PageDown,
+    // a digit of the numeric keypad
+    Numpad(u8),
+    Other,
+}
+
+// The mouse cursor cell, and the buttons clicked since the last event.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MouseState {
+    pub x: i32,
+    pub y: i32,
+    pub lbutton_pressed: bool,
+    pub rbutton_pressed: bool,
+}
+
+// The next command, from the replay or from a key press. Keys bound to nothing give None.
+pub fn wait_for_command(frontend: &mut Frontend, player_alive: bool) -> Option<Command> {
+    next_input(
+        frontend,
+        RecordedInput::command,
+        |frontend| {
+            let key = frontend.screen.wait_for_key();
+            frontend.keymap.command(key, player_alive)
+        },
+        RecordedInput::Command,
+    )
+}
+
+pub fn handle_keys(
+    frontend: &mut Frontend,
+    game: &mut Game,
+    objects: &mut Objects,
+) -> PlayerAction {
+    use Command::*;
+
+    let player_alive = objects.is_alive(PLAYER);
+    let command = match wait_for_command(frontend, player_alive) {
+        Some(command) => command,
+        None => return PlayerAction::DidntTakeTurn,
+    };
+    match command {
+        PickUp => {
+            let player_pos = objects.pos(PLAYER);
+            let item_id = objects
+                .items
+                .ids()
+                .into_iter()
+                .find(|&id| objects.positions.get(id) == Some(&player_pos));
+            if let Some(item_id) = item_id {
+                pick_item_up(item_id, game, objects);
+            }
+            PlayerAction::DidntTakeTurn
+        }
+        Drop => {
+            let item_id = inventory_menu(
+                &objects.inventories[PLAYER],
+                objects,
+                "Press the key next to an item to drop it, or any other to cancel.\n'",
+                frontend,
+            );
+            if let Some(item_id) = item_id {
+                drop_item(item_id, game, objects);
+            }
+            PlayerAction::DidntTakeTurn
+        }
+        Inventory => {
+            let inventory_index = inventory_menu(
+                &objects.inventories[PLAYER],
+                objects,
+                "Press the key next to an item to use it, or any other to cancel.\n",
+                frontend,
+            );
+            let result = inventory_index.map(|inventory_index| {
+                use_inventory_item(inventory_index, frontend, game, objects)
+            });
+            match result {
+                Some(UseResult::UsedUp | UseResult::UsedAndKept) => {
+                    PlayerAction::TookTurn(Action::UseItem)
+                }
+                _ => PlayerAction::DidntTakeTurn,
+            }
+        }
+        Descend => {
+            take_stairs(game, objects);
+            PlayerAction::DidntTakeTurn
+        }
+        Help => {
+            // nobody is there to close it during a replay
+            if frontend.replay.is_none() {
+                help_screen(frontend);
+            }
+            PlayerAction::DidntTakeTurn
+        }
+        MessageLog => {
+            if frontend.replay.is_none() {
+                message_log(frontend, &game.messages);
+            }
+            PlayerAction::DidntTakeTurn
+        }
+        Restart => PlayerAction::Replay,
+        MoveUp | MoveDown | MoveLeft | MoveRight | MoveUpLeft | MoveUpRight | MoveDownLeft
+        | MoveDownRight => {
+            let (dx, dy) = command.direction().unwrap_or((0, 0));
+            PlayerAction::TookTurn(player_move_or_attack(dx, dy, game, objects))
+        }
+        Wait => PlayerAction::TookTurn(Action::Wait),
+        ToggleFullscreen => {
+            frontend.screen.toggle_fullscreen();
+            PlayerAction::DidntTakeTurn
+        }
+        Exit => PlayerAction::Exit,
+    }
+}
+
+// Ask the player for whatever target the item needs before handing it to the engine.
+fn use_inventory_item(
+    inventory_index: usize,
+    frontend: &mut Frontend,
+    game: &mut Game,
+    objects: &mut Objects,
+) -> UseResult {
+    let item_id = objects.inventories[PLAYER][inventory_index];
+    let targeting = objects
+        .items
+        .get(item_id)
+        .map_or(Targeting::None, |item| item.targeting());
+    let target = match targeting {
+        Targeting::None => None,
+        Targeting::Monster { max_range } => {
+            game.messages.add(
+                "Left-click an enemy to target it, or right-click/Esc to cancel.",
+                LIGHT_CYAN,
+            );
+            next_input(
+                frontend,
+                RecordedInput::target,
+                |frontend| {
+                    target_monster(frontend, game, objects, Some(max_range as f32))
+                        .map(|id| objects.pos(id))
+                },
+                RecordedInput::Target,
+            )
+        }
+        Targeting::Tile => {
+            game.messages.add(
+                "Left-click a target tile, or right-click/Esc to cancel.",
+                LIGHT_CYAN,
+            );
+            next_input(
+                frontend,
+                RecordedInput::target,
+                |frontend| target_tile(frontend, game, objects, None),
+                RecordedInput::Target,
+            )
+        }
+    };
+    use_item(inventory_index, target, game, objects)
+}
+
+// Let the player pick a tile with the mouse or a keyboard cursor. The tile has to be in
+// FOV and, when `max_range` is given, within that distance of the player.
+pub fn target_tile(
+    frontend: &mut Frontend,
+    game: &Game,
+    objects: &Objects,
+    max_range: Option<f32>,
+) -> Option<(i32, i32)> {
+    let is_valid = |game: &Game, (x, y): (i32, i32)| {
+        player_sees(x, y, game, objects)
+            && max_range.is_none_or(|range| objects.distance(PLAYER, x, y) <= range)
+    };
+    // a click that ended an earlier targeting must not end this one
+    frontend.mouse.lbutton_pressed = false;
+    frontend.mouse.rbutton_pressed = false;
+    let mut cursor = objects.pos(PLAYER);
+    loop {
+        let highlight = if is_valid(game, cursor) {
+            LIGHT_GREEN
+        } else {
+            RED
+        };
+        render_all(frontend, game, objects);
+        let (camera_x, camera_y) = camera(frontend.screen.as_ref(), objects);
+        frontend
+            .screen
+            .set_background(cursor.0 - camera_x, cursor.1 - camera_y, highlight);
+        frontend.screen.flush();
+
+        let mut key = None;
+        match frontend.screen.check_for_event() {
+            Some(InputEvent::Mouse(mouse)) => {
+                frontend.mouse = mouse;
+                if let Some(mouse_pos) = screen_to_map(frontend, objects, (mouse.x, mouse.y)) {
+                    cursor = mouse_pos;
+                }
+            }
+            Some(InputEvent::Key(k)) => key = Some(k),
+            None => {}
+        }
+
+        if frontend.mouse.rbutton_pressed {
+            return None;
+        }
+        if frontend.mouse.lbutton_pressed && is_valid(game, cursor) {
+            return Some(cursor);
+        }
+        // a click is only reported once, don't let it confirm a later keyboard move
+        frontend.mouse.lbutton_pressed = false;
+        let (dx, dy) = match key {
+            Some(KeyPress {
+                name: KeyName::Escape,
+                ..
+            }) => return None,
+            Some(KeyPress {
+                name: KeyName::Enter,
+                ..
+            }) if is_valid(game, cursor) => return Some(cursor),
+            // the cursor moves with the same keys as the player
+            Some(key) => frontend
+                .keymap
+                .command(key, true)
+                .and_then(Command::direction)
+                .unwrap_or((0, 0)),
+            None => (0, 0),
+        };
+        cursor = (
+            (cursor.0 + dx).clamp(0, MAP_WIDTH - 1),
+            (cursor.1 + dy).clamp(0, MAP_HEIGHT - 1),
+        );
+    }
+}
+
+pub fn target_monster(
+    frontend: &mut Frontend,
+    game: &Game,
+    objects: &Objects,
+    max_range: Option<f32>,
+) -> Option<Entity> {
+    loop {
+        let (x, y) = target_tile(frontend, game, objects, max_range)?;
+        let monster_id = objects.fighter_at(x, y).filter(|&id| id != PLAYER);
+        if monster_id.is_some() {
+            return monster_id;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::engine::new_game;
+    use crate::frontend::renderer::grid::GridRenderer;
+
+    // Two targetings in a row, the first ended by a click on the player and the second
+    // by pressing `key`.
+    fn target_twice(left: bool, key: KeyName) -> [Option<(i32, i32)>; 2] {
+        let (game, objects) = new_game(1, Default::default(), Rc::new(Default::default()));
+        let mut screen = GridRenderer::new(40, 20);
+        let (camera_x, camera_y) = camera(&screen, &objects);
+        let (player_x, player_y) = objects.pos(PLAYER);
+        screen.click(MouseState {
+            x: player_x - camera_x,
+            y: player_y - camera_y,
+            lbutton_pressed: left,
+            rbutton_pressed: !left,
+        });
+        screen.press(KeyPress {
+            name: key,
+            ctrl: false,
+            alt: false,
+            shift: false,
+        });
+        let mut frontend = Frontend {
+            screen: Box::new(screen),
+            mouse: Default::default(),
+            recording: None,
+            replay: None,
+            keymap: Default::default(),
+        };
+        [(); 2].map(|_| target_tile(&mut frontend, &game, &objects, None))
+    }
+
+    #[test]
+    fn a_left_click_does_not_confirm_the_next_target() {
+        let [first, second] = target_twice(true, KeyName::Escape);
+        assert!(first.is_some());
+        assert_eq!(second, None);
+    }
+
+    #[test]
+    fn a_right_click_does_not_cancel_the_next_target() {
+        let [first, second] = target_twice(false, KeyName::Enter);
+        assert_eq!(first, None);
+        // Enter confirms the cursor, which starts on the player
+        assert!(second.is_some());
+    }
+}
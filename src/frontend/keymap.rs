@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+
+// 3party
+use serde::{Deserialize, Serialize};
+
+use super::input::{KeyName, KeyPress};
+
+// Optional, every command missing from it keeps its default keys.
+pub const KEYMAP_FILE: &str = "keymap.ron";
+
+// Everything the player can ask for from the map screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Command {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    MoveUpLeft,
+    MoveUpRight,
+    MoveDownLeft,
+    MoveDownRight,
+    Wait,
+    PickUp,
+    Drop,
+    Inventory,
+    Descend,
+    MessageLog,
+    Help,
+    Restart,
+    ToggleFullscreen,
+    Exit,
+}
+
+// Keys written like in the keymap file: a lowercase letter for the key itself, any other
+// character for the text a key combination types, or a key name, with optional `Ctrl+`
+// and `Alt+` prefixes. `Num0` to `Num9` are the digits of the numeric keypad.
+const DEFAULT_KEYS: &[(Command, &[&str])] = &[
+    (Command::MoveUp, &["w", "Up", "k", "Num8"]),
+    (Command::MoveDown, &["s", "Down", "j", "Num2"]),
+    (Command::MoveLeft, &["a", "Left", "h", "Num4"]),
+    (Command::MoveRight, &["d", "Right", "l", "Num6"]),
+    (Command::MoveUpLeft, &["y", "Num7"]),
+    (Command::MoveUpRight, &["u", "Num9"]),
+    (Command::MoveDownLeft, &["b", "Num1"]),
+    (Command::MoveDownRight, &["n", "Num3"]),
+    (Command::Wait, &[".", "Num5"]),
+    (Command::PickUp, &["g"]),
+    (Command::Drop, &["f"]),
+    (Command::Inventory, &["i"]),
+    (Command::Descend, &[">"]),
+    (Command::MessageLog, &["m"]),
+    (Command::Help, &["?"]),
+    (Command::Restart, &["y"]),
+    (Command::ToggleFullscreen, &["Ctrl+Enter"]),
+    (Command::Exit, &["Escape"]),
+];
+
+impl Command {
+    pub fn description(self) -> &'static str {
+        use Command::*;
+        match self {
+            MoveUp => "move or attack up",
+            MoveDown => "move or attack down",
+            MoveLeft => "move or attack left",
+            MoveRight => "move or attack right",
+            MoveUpLeft => "move or attack up left",
+            MoveUpRight => "move or attack up right",
+            MoveDownLeft => "move or attack down left",
+            MoveDownRight => "move or attack down right",
+            Wait => "wait a turn",
+            PickUp => "pick up an item",
+            Drop => "drop an item",
+            Inventory => "use an item",
+            Descend => "take the stairs down",
+            MessageLog => "show the message log",
+            Help => "show this help",
+            Restart => "start a new game after dying",
+            ToggleFullscreen => "toggle fullscreen",
+            Exit => "save and quit",
+        }
+    }
+
+    pub fn direction(self) -> Option<(i32, i32)> {
+        use Command::*;
+        match self {
+            MoveUp => Some((0, -1)),
+            MoveDown => Some((0, 1)),
+            MoveLeft => Some((-1, 0)),
+            MoveRight => Some((1, 0)),
+            MoveUpLeft => Some((-1, -1)),
+            MoveUpRight => Some((1, -1)),
+            MoveDownLeft => Some((-1, 1)),
+            MoveDownRight => Some((1, 1)),
+            _ => None,
+        }
+    }
+
+    // A key may be bound to a command for the living player and another for the dead one.
+    pub fn is_available(self, player_alive: bool) -> bool {
+        use Command::*;
+        match self {
+            Restart => !player_alive,
+            MessageLog | Help | ToggleFullscreen | Exit => true,
+            _ => player_alive,
+        }
+    }
+}
+
+// A key as written in the keymap. Shift is left out: it is part of the typed text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub name: KeyName,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl KeyBinding {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut binding = KeyBinding {
+            name: KeyName::Other,
+            ctrl: false,
+            alt: false,
+        };
+        let mut rest = text;
+        loop {
+            if let Some(key) = rest.strip_prefix("Ctrl+") {
+                binding.ctrl = true;
+                rest = key;
+            } else if let Some(key) = rest.strip_prefix("Alt+") {
+                binding.alt = true;
+                rest = key;
+            } else {
+                break;
+            }
+        }
+
+        let mut chars = rest.chars();
+        binding.name = match (chars.next(), chars.next()) {
+            (Some(c), None) if c.is_ascii_lowercase() => KeyName::Char(c),
+            (Some(c), None) => KeyName::Text(c),
+            _ => match rest {
+                "Up" => KeyName::Up,
+                "Down" => KeyName::Down,
+                "Left" => KeyName::Left,
+                "Right" => KeyName::Right,
+                "Enter" => KeyName::Enter,
+                "Escape" => KeyName::Escape,
+                "Backspace" => KeyName::Backspace,
+                "PageUp" => KeyName::PageUp,
+                "PageDown" => KeyName::PageDown,
+                _ => match rest.strip_prefix("Num").map(str::parse) {
+                    Some(Ok(digit @ 0..=9)) => KeyName::Numpad(digit),
+                    _ => return Err(format!("unknown key \"{}\"", text)),
+                },
+            },
+        };
+        Ok(binding)
+    }
+
+    pub fn matches(&self, key: KeyPress) -> bool {
+        self.name == key.name && self.ctrl == key.ctrl && self.alt == key.alt
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        match self.name {
+            KeyName::Char(c) | KeyName::Text(c) => write!(f, "{}", c),
+            KeyName::Numpad(digit) => write!(f, "Num{}", digit),
+            name => write!(f, "{:?}", name),
+        }
+    }
+}
+
+// The keys of every command, in the order of `DEFAULT_KEYS`.
+pub struct Keymap {
+    bindings: Vec<(Command, Vec<KeyBinding>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = DEFAULT_KEYS
+            .iter()
+            .map(|&(command, keys)| {
+                let keys = keys.iter().map(|key| KeyBinding::parse(key).unwrap());
+                (command, keys.collect())
+            })
+            .collect();
+        Keymap { bindings }
+    }
+}
+
+impl Keymap {
+    // The defaults, with the commands listed in `path` rebound.
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Keymap::default()),
+            Err(e) => return Err(format!("{}: {}", path, e).into()),
+        };
+        let config: HashMap<Command, Vec<String>> =
+            ron::from_str(&text).map_err(|e| format!("{}:{}", path, e))?;
+
+        let mut keymap = Keymap::default();
+        for (command, keys) in keymap.bindings.iter_mut() {
+            if let Some(config_keys) = config.get(command) {
+                *keys = config_keys
+                    .iter()
+                    .map(|key| KeyBinding::parse(key))
+                    .collect::<Result<_, _>>()
+                    .map_err(|e| format!("{}: {:?}: {}", path, command, e))?;
+            }
+        }
+        Ok(keymap)
+    }
+
+    pub fn command(&self, key: KeyPress, player_alive: bool) -> Option<Command> {
+        self.bindings
+            .iter()
+            .filter(|(command, _)| command.is_available(player_alive))
+            .find(|(_, keys)| keys.iter().any(|binding| binding.matches(key)))
+            .map(|&(command, _)| command)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(Command, Vec<KeyBinding>)> {
+        self.bindings.iter()
+    }
+}
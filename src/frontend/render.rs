@@ -0,0 +1,398 @@
+use tcod::colors::DARKER_RED;
+use tcod::colors::DARKER_VIOLET;
+use tcod::colors::LIGHT_GREY;
+use tcod::colors::LIGHT_RED;
+use tcod::colors::LIGHT_VIOLET;
+use tcod::colors::WHITE;
+use tcod::Color;
+
+use crate::engine::entity::*;
+use crate::engine::map::*;
+use crate::engine::object::*;
+use crate::engine::status::Status;
+use crate::engine::{player_sees, Game};
+
+use super::input::MouseState;
+use super::keymap::Keymap;
+use super::renderer::Renderer;
+use super::replay::{Recording, Replay};
+
+pub const BAR_WIDTH: i32 = 20;
+pub const PANEL_HEIGHT: i32 = 7;
+
+pub const MSG_X: i32 = BAR_WIDTH + 2;
+pub const MSG_HEIGHT: usize = PANEL_HEIGHT as usize - 1;
+
+pub const COLOR_DARK_WALL: Color = Color { r: 0, g: 0, b: 100 };
+pub const COLOR_LIGHT_WALL: Color = Color {
+    r: 130,
+    g: 110,
+    b: 50,
+};
+pub const COLOR_DARK_GROUND: Color = Color {
+    r: 50,
+    g: 50,
+    b: 150,
+};
+pub const COLOR_LIGHT_GROUND: Color = Color {
+    r: 200,
+    g: 180,
+    b: 50,
+};
+
+pub struct Frontend {
+    pub screen: Box<dyn Renderer>,
+    pub mouse: MouseState,
+    pub recording: Option<Recording>,
+    pub replay: Option<Replay>,
+    pub keymap: Keymap,
+}
+
+// The map tile in the top left corner of the screen. A screen smaller than the map
+// follows the player.
+pub fn camera(screen: &dyn Renderer, objects: &Objects) -> (i32, i32) {
+    let (width, height) = screen.size();
+    let view_height = height - PANEL_HEIGHT;
+    let (player_x, player_y) = objects.pos(PLAYER);
+    (
+        (player_x - width / 2).clamp(0, (MAP_WIDTH - width).max(0)),
+        (player_y - view_height / 2).clamp(0, (MAP_HEIGHT - view_height).max(0)),
+    )
+}
+
+// The map tile under a screen cell, if the cell shows the map.
+pub fn screen_to_map(
+    frontend: &Frontend,
+    objects: &Objects,
+    (x, y): (i32, i32),
+) -> Option<(i32, i32)> {
+    let (_, height) = frontend.screen.size();
+    let (camera_x, camera_y) = camera(frontend.screen.as_ref(), objects);
+    let (map_x, map_y) = (x + camera_x, y + camera_y);
+    let on_map = (0..MAP_WIDTH).contains(&map_x) && (0..MAP_HEIGHT).contains(&map_y);
+    (y < height - PANEL_HEIGHT && x >= 0 && y >= 0 && on_map).then_some((map_x, map_y))
+}
+
+pub fn render_all(frontend: &mut Frontend, game: &Game, objects: &Objects) {
+    let screen = frontend.screen.as_mut();
+    screen.clear();
+    let (width, height) = screen.size();
+    let view_height = height - PANEL_HEIGHT;
+    let (camera_x, camera_y) = camera(screen, objects);
+    let in_view = |(x, y): (i32, i32)| {
+        (camera_x..camera_x + width).contains(&x) && (camera_y..camera_y + view_height).contains(&y)
+    };
+
+    for y in camera_y..(camera_y + view_height).min(MAP_HEIGHT) {
+        for x in camera_x..(camera_x + width).min(MAP_WIDTH) {
+            let visible = player_sees(x, y, game, objects);
+            let wall = game.map[x as usize][y as usize].block_sight;
+            let color = match (visible, wall) {
+                (false, true) => COLOR_DARK_WALL,
+                (false, false) => COLOR_DARK_GROUND,
+                (true, true) => COLOR_LIGHT_WALL,
+                (true, false) => COLOR_LIGHT_GROUND,
+            };
+            if game.map[x as usize][y as usize].explored {
+                screen.set_background(x - camera_x, y - camera_y, color);
+            }
+        }
+    }
+    let mut to_draw: Vec<_> = objects
+        .renderables
+        .iter()
+        .filter_map(|(id, renderable)| Some((*objects.positions.get(id)?, renderable, id)))
+        .filter(|&(pos, _, _)| in_view(pos))
+        .filter(|&((x, y), renderable, _)| {
+            player_sees(x, y, game, objects)
+                || (renderable.always_visible && game.map[x as usize][y as usize].explored)
+        })
+        .collect();
+    // blocking objects are drawn last, on top of the items they stand on
+    to_draw.sort_by_key(|&(_, _, id)| objects.blockers.contains(id));
+    for ((x, y), renderable, _) in to_draw {
+        draw_object((x - camera_x, y - camera_y), renderable, screen);
+    }
+
+    // show bottom panel
+    let panel_y = view_height;
+    let hp = objects.fighters.get(PLAYER).map_or(0, |f| f.hp);
+    let max_hp = objects.max_hp(PLAYER);
+    render_bar(
+        screen,
+        1,
+        panel_y + 1,
+        BAR_WIDTH,
+        "HP",
+        hp,
+        max_hp,
+        LIGHT_RED,
+        DARKER_RED,
+    );
+    let xp = objects.fighters.get(PLAYER).map_or(0, |f| f.xp);
+    let level_up_xp = level_up_xp(objects.levels[PLAYER]);
+    render_bar(
+        screen,
+        1,
+        panel_y + 2,
+        BAR_WIDTH,
+        "XP",
+        xp,
+        level_up_xp,
+        LIGHT_VIOLET,
+        DARKER_VIOLET,
+    );
+    screen.print(
+        1,
+        panel_y + 3,
+        &format!("Dungeon level: {}", game.dungeon_level),
+        WHITE,
+    );
+    screen.print(
+        1,
+        panel_y + 4,
+        &format!("Character level: {}", objects.levels[PLAYER]),
+        WHITE,
+    );
+    screen.print(1, panel_y + 5, &format!("Turn: {}", game.turn()), WHITE);
+    if objects.is_alive(PLAYER) {
+        let statuses = objects.statuses.get(PLAYER).into_iter().flatten();
+        render_statuses(screen, 1, panel_y + 6, statuses.map(|effect| effect.status));
+    } else {
+        screen.print(1, panel_y + 6, &format!("Seed: {}", game.seed), WHITE);
+    }
+
+    let msg_width = width - MSG_X;
+    let mut y = MSG_HEIGHT as i32;
+    for message in game.messages.iter().rev() {
+        let lines = wrap_text(&message.full_text(), msg_width);
+        y -= lines.len() as i32;
+        if y < 0 {
+            break;
+        }
+        for (row, line) in lines.iter().enumerate() {
+            screen.print(MSG_X, panel_y + y + row as i32, line, message.color);
+        }
+    }
+    let names = get_names_under_mouse(frontend, game, objects);
+    frontend.screen.print(1, panel_y, &names, LIGHT_GREY);
+}
+#[allow(clippy::too_many_arguments)]
+fn render_bar(
+    screen: &mut dyn Renderer,
+    x: i32,
+    y: i32,
+    total_width: i32,
+    name: &str,
+    value: i32,
+    maximum: i32,
+    bar_color: Color,
+    back_color: Color,
+) {
+    let bar_width = (value as f32 / maximum as f32 * total_width as f32) as i32;
+    for bar_x in x..(x + total_width) {
+        let color = if bar_x < x + bar_width {
+            bar_color
+        } else {
+            back_color
+        };
+        screen.set_background(bar_x, y, color);
+    }
+
+    let text = format!("{} {}/{}", name, value, maximum);
+    let text_x = x + total_width / 2 - text.chars().count() as i32 / 2;
+    screen.print(text_x, y, &text, WHITE);
+}
+
+// The labels in their colors on one line, as many as fit left of the messages.
+fn render_statuses(
+    screen: &mut dyn Renderer,
+    x: i32,
+    y: i32,
+    statuses: impl Iterator<Item = Status>,
+) {
+    let mut label_x = x;
+    for status in statuses {
+        let label = status.label();
+        if label_x + label.len() as i32 >= MSG_X {
+            break;
+        }
+        screen.print(label_x, y, label, status.color());
+        label_x += label.len() as i32 + 1;
+    }
+}
+
+pub fn draw_object((x, y): (i32, i32), renderable: &Renderable, screen: &mut dyn Renderer) {
+    screen.put_char(x, y, renderable.char, renderable.color);
+}
+
+// Split `text` into lines of at most `width` characters, breaking between words where
+// possible. Line breaks in the text are kept.
+pub fn wrap_text(text: &str, width: i32) -> Vec<String> {
+    let width = width.max(1) as usize;
+    let mut lines = vec![];
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let line_length = line.chars().count();
+            if line_length > 0 && line_length + 1 + word.chars().count() > width {
+                lines.push(std::mem::take(&mut line));
+            } else if line_length > 0 {
+                line.push(' ');
+            }
+            line.push_str(word);
+            // a word longer than the line is cut
+            while line.chars().count() > width {
+                let rest = line.chars().skip(width).collect();
+                line = line.chars().take(width).collect();
+                lines.push(std::mem::replace(&mut line, rest));
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn get_names_under_mouse(frontend: &Frontend, game: &Game, objects: &Objects) -> String {
+    let (x, y) = match screen_to_map(frontend, objects, (frontend.mouse.x, frontend.mouse.y)) {
+        Some(pos) => pos,
+        None => return String::new(),
+    };
+
+    let names = objects
+        .positions
+        .iter()
+        .filter(|&(_, &pos)| pos == (x, y) && player_sees(x, y, game, objects))
+        .map(|(id, _)| objects.name(id))
+        .collect::<Vec<_>>();
+
+    names.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+    use std::rc::Rc;
+
+    use rand::SeedableRng;
+    use tcod::colors::{BLACK, GREEN, RED};
+
+    use super::*;
+    use crate::engine::combat::Dice;
+    use crate::engine::status::{set_status, StatusEffect};
+    use crate::engine::{update_fov, GameRng, Messages};
+    use crate::frontend::renderer::grid::GridRenderer;
+
+    // a lit 5x3 room with the player in it, surrounded by rock
+    fn small_level() -> (Game, Objects) {
+        let mut map = vec![vec![Tile::wall(); MAP_HEIGHT as usize]; MAP_WIDTH as usize];
+        for column in &mut map[10..15] {
+            for tile in &mut column[10..13] {
+                *tile = Tile::empty();
+            }
+        }
+        let mut game = Game {
+            map,
+            messages: Messages::new(),
+            dungeon_level: 3,
+            seed: 42,
+            time: 57,
+            rng: GameRng::seed_from_u64(42),
+            generator: Default::default(),
+            fov: Default::default(),
+            content: Rc::new(Default::default()),
+        };
+        let mut objects = Objects::new();
+        let player = objects.spawn(12, 11, '@', "player", WHITE, true);
+        objects.fighters.insert(
+            player,
+            Fighter {
+                base_max_hp: 30,
+                hp: 20,
+                base_defense: 2,
+                base_power: 5,
+                damage: Dice::parse("1d6+2").unwrap(),
+                xp: 10,
+                on_death: DeathCallback::Player,
+                on_hit: None,
+            },
+        );
+        objects.inventories.insert(player, vec![]);
+        objects.levels.insert(player, 2);
+        for status in [Status::Poisoned, Status::Hasted] {
+            set_status(player, StatusEffect { status, turns: 5 }, &mut objects);
+        }
+        objects.spawn(14, 12, 'o', "orc", GREEN, true);
+        // beyond the wall, out of sight
+        objects.spawn(30, 11, '!', "potion", RED, false);
+        update_fov(&mut game, &objects);
+        game.messages.add("Welcome!", RED);
+        game.messages
+            .add("The orc hits you for 10 hit points.", RED);
+        (game, objects)
+    }
+
+    fn frontend(width: i32, height: i32) -> Frontend {
+        Frontend {
+            screen: Box::new(GridRenderer::new(width, height)),
+            mouse: Default::default(),
+            recording: None,
+            replay: None,
+            keymap: Default::default(),
+        }
+    }
+
+    #[test]
+    fn render_bar_fills_its_share() {
+        let mut screen = GridRenderer::new(12, 1);
+        render_bar(&mut screen, 1, 0, 10, "HP", 3, 10, LIGHT_RED, DARKER_RED);
+        assert_eq!(screen.to_ascii(), "   HP 3/10");
+        let backgrounds: Vec<_> = (0..12).map(|x| screen.cell(x, 0).unwrap().bg).collect();
+        let mut expected = vec![BLACK];
+        expected.extend([LIGHT_RED; 3]);
+        expected.extend([DARKER_RED; 7]);
+        expected.push(BLACK);
+        assert_eq!(backgrounds, expected);
+    }
+
+    #[test]
+    fn render_all_draws_map_and_panel() {
+        let (game, objects) = small_level();
+        let mut frontend = frontend(40, 14);
+        // over the orc
+        frontend.mouse = MouseState {
+            x: 14,
+            y: 4,
+            ..Default::default()
+        };
+        render_all(&mut frontend, &game, &objects);
+
+        let screen: &dyn Any = frontend.screen.as_ref();
+        let screen = screen.downcast_ref::<GridRenderer>().unwrap();
+        assert_eq!(
+            screen.to_ascii(),
+            [
+                "",
+                "",
+                "",
+                "            @",
+                "              o",
+                "",
+                "",
+                " orc",
+                "       HP 20/30",
+                "       XP 10/500",
+                " Dungeon level: 3     Welcome!",
+                " Character level: 2   The orc hits you",
+                " Turn: 5              for 10 hit points.",
+                " Pois Haste",
+            ]
+            .join("\n")
+        );
+        // the camera starts at map row 8
+        let background = |x, y| screen.cell(x, y).unwrap().bg;
+        assert_eq!(background(12, 3), COLOR_LIGHT_GROUND);
+        assert_eq!(background(9, 3), COLOR_LIGHT_WALL);
+        assert_eq!(background(30, 3), BLACK);
+    }
+}
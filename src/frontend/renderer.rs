@@ -0,0 +1,53 @@
+pub mod grid;
+pub mod sdl;
+pub mod terminal;
+
+use std::any::Any;
+
+use tcod::Color;
+
+use super::input::{KeyPress, MouseState};
+use grid::GridRenderer;
+
+#[derive(Clone, Copy, Debug)]
+pub enum InputEvent {
+    Key(KeyPress),
+    Mouse(MouseState),
+}
+
+// Where the game is drawn and its input comes from, so the screens are written once for
+// the tcod window and the terminal. Coordinates are in cells, cells off the screen are
+// ignored. A `GridRenderer` behind a `dyn Renderer` can be downcast to read it back.
+pub trait Renderer: Any {
+    fn size(&self) -> (i32, i32);
+    // blank cells on a black background
+    fn clear(&mut self);
+    fn put_char(&mut self, x: i32, y: i32, c: char, color: Color);
+    fn set_background(&mut self, x: i32, y: i32, color: Color);
+    // nothing shows up until the next flush
+    fn flush(&mut self);
+    fn wait_for_key(&mut self) -> KeyPress;
+    // the next key press or mouse event, if one comes in soon
+    fn check_for_event(&mut self) -> Option<InputEvent>;
+    fn window_closed(&self) -> bool;
+    fn toggle_fullscreen(&mut self) {}
+
+    fn print(&mut self, x: i32, y: i32, text: &str, color: Color) {
+        for (i, c) in text.chars().enumerate() {
+            self.put_char(x + i as i32, y, c, color);
+        }
+    }
+
+    // copy an offscreen window onto this one with its top left corner at (x, y)
+    fn blit(&mut self, window: &GridRenderer, x: i32, y: i32) {
+        let (width, height) = window.size();
+        for window_y in 0..height {
+            for window_x in 0..width {
+                if let Some(cell) = window.cell(window_x, window_y) {
+                    self.put_char(x + window_x, y + window_y, cell.c, cell.fg);
+                    self.set_background(x + window_x, y + window_y, cell.bg);
+                }
+            }
+        }
+    }
+}
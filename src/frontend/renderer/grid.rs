@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+
+use tcod::colors::{BLACK, WHITE};
+use tcod::Color;
+
+use super::{InputEvent, Renderer};
+use crate::frontend::input::{KeyName, KeyPress, MouseState};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cell {
+    pub c: char,
+    pub fg: Color,
+    pub bg: Color,
+}
+
+pub const BLANK: Cell = Cell {
+    c: ' ',
+    fg: WHITE,
+    bg: BLACK,
+};
+
+// Cells kept in memory: an offscreen window to blit onto the screen, the buffer behind
+// the terminal, or a screen for tests to compare against ASCII. Its input is whatever
+// keys were pressed and clicks made on it beforehand.
+#[derive(Clone, Debug)]
+pub struct GridRenderer {
+    width: i32,
+    height: i32,
+    cells: Vec<Cell>,
+    events: VecDeque<InputEvent>,
+}
+
+impl GridRenderer {
+    pub fn new(width: i32, height: i32) -> Self {
+        let (width, height) = (width.max(0), height.max(0));
+        GridRenderer {
+            width,
+            height,
+            cells: vec![BLANK; (width * height) as usize],
+            events: VecDeque::new(),
+        }
+    }
+
+    pub fn cell(&self, x: i32, y: i32) -> Option<Cell> {
+        self.index(x, y).map(|index| self.cells[index])
+    }
+
+    // row-major, `width` cells per row
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    // queue a key for the next `wait_for_key` or `check_for_event`
+    pub fn press(&mut self, key: KeyPress) {
+        self.events.push_back(InputEvent::Key(key));
+    }
+
+    // queue a mouse event for the next `check_for_event`, `wait_for_key` skips it
+    pub fn click(&mut self, mouse: MouseState) {
+        self.events.push_back(InputEvent::Mouse(mouse));
+    }
+
+    // The characters, a line per row without the trailing blanks.
+    pub fn to_ascii(&self) -> String {
+        self.cells
+            .chunks(self.width.max(1) as usize)
+            .map(|row| {
+                let line: String = row.iter().map(|cell| cell.c).collect();
+                line.trim_end().to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        if (0..self.width).contains(&x) && (0..self.height).contains(&y) {
+            Some((x + y * self.width) as usize)
+        } else {
+            None
+        }
+    }
+}
+
+impl Renderer for GridRenderer {
+    fn size(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+    fn clear(&mut self) {
+        self.cells.fill(BLANK);
+    }
+    fn put_char(&mut self, x: i32, y: i32, c: char, color: Color) {
+        if let Some(index) = self.index(x, y) {
+            self.cells[index].c = c;
+            self.cells[index].fg = color;
+        }
+    }
+    fn set_background(&mut self, x: i32, y: i32, color: Color) {
+        if let Some(index) = self.index(x, y) {
+            self.cells[index].bg = color;
+        }
+    }
+    fn flush(&mut self) {}
+    fn wait_for_key(&mut self) -> KeyPress {
+        while let Some(event) = self.events.pop_front() {
+            if let InputEvent::Key(key) = event {
+                return key;
+            }
+        }
+        // out of keys, back out of whatever is waiting
+        KeyPress {
+            name: KeyName::Escape,
+            ctrl: false,
+            alt: false,
+            shift: false,
+        }
+    }
+    fn check_for_event(&mut self) -> Option<InputEvent> {
+        self.events.pop_front()
+    }
+    fn window_closed(&self) -> bool {
+        false
+    }
+}
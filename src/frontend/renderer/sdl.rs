@@ -0,0 +1,116 @@
+use tcod::colors::BLACK;
+use tcod::console::*;
+use tcod::input::{self, Event, Key, KeyCode};
+use tcod::Color;
+
+use super::{InputEvent, Renderer};
+use crate::frontend::input::{KeyName, KeyPress, MouseState};
+
+pub const LIMIT_FPS: i32 = 20;
+
+// The tcod window, drawn with the bundled font.
+pub struct TcodRenderer {
+    root: Root,
+}
+
+impl TcodRenderer {
+    pub fn new(width: i32, height: i32) -> Self {
+        let root = Root::initializer()
+            .font("arial10x10.png", FontLayout::Tcod)
+            .font_type(FontType::Greyscale)
+            .size(width, height)
+            .title("Roguelike!")
+            .init();
+        tcod::system::set_fps(LIMIT_FPS);
+        TcodRenderer { root }
+    }
+}
+
+impl Renderer for TcodRenderer {
+    fn size(&self) -> (i32, i32) {
+        (self.root.width(), self.root.height())
+    }
+    fn clear(&mut self) {
+        self.root.set_default_background(BLACK);
+        self.root.clear();
+    }
+    fn put_char(&mut self, x: i32, y: i32, c: char, color: Color) {
+        let (width, height) = self.size();
+        if (0..width).contains(&x) && (0..height).contains(&y) {
+            self.root.set_char(x, y, c);
+            self.root.set_char_foreground(x, y, color);
+        }
+    }
+    fn set_background(&mut self, x: i32, y: i32, color: Color) {
+        let (width, height) = self.size();
+        if (0..width).contains(&x) && (0..height).contains(&y) {
+            self.root
+                .set_char_background(x, y, color, BackgroundFlag::Set);
+        }
+    }
+    fn flush(&mut self) {
+        self.root.flush();
+    }
+    fn wait_for_key(&mut self) -> KeyPress {
+        KeyPress::from(self.root.wait_for_keypress(true))
+    }
+    fn check_for_event(&mut self) -> Option<InputEvent> {
+        match input::check_for_event(input::KEY_PRESS | input::MOUSE) {
+            Some((_, Event::Key(key))) => Some(InputEvent::Key(KeyPress::from(key))),
+            Some((_, Event::Mouse(mouse))) => Some(InputEvent::Mouse(MouseState {
+                x: mouse.cx as i32,
+                y: mouse.cy as i32,
+                lbutton_pressed: mouse.lbutton_pressed,
+                rbutton_pressed: mouse.rbutton_pressed,
+            })),
+            None => None,
+        }
+    }
+    fn window_closed(&self) -> bool {
+        self.root.window_closed()
+    }
+    fn toggle_fullscreen(&mut self) {
+        let fullscreen = self.root.is_fullscreen();
+        self.root.set_fullscreen(!fullscreen);
+    }
+}
+
+impl From<Key> for KeyPress {
+    fn from(key: Key) -> Self {
+        let name = match key.code {
+            KeyCode::Char => KeyName::Char(key.printable),
+            KeyCode::Text => key
+                .text()
+                .chars()
+                .next()
+                .map_or(KeyName::Other, KeyName::Text),
+            KeyCode::Spacebar => KeyName::Char(' '),
+            KeyCode::Up => KeyName::Up,
+            KeyCode::Down => KeyName::Down,
+            KeyCode::Left => KeyName::Left,
+            KeyCode::Right => KeyName::Right,
+            KeyCode::Enter => KeyName::Enter,
+            KeyCode::Escape => KeyName::Escape,
+            KeyCode::Backspace => KeyName::Backspace,
+            KeyCode::PageUp => KeyName::PageUp,
+            KeyCode::PageDown => KeyName::PageDown,
+            KeyCode::NumPad0 => KeyName::Numpad(0),
+            KeyCode::NumPad1 => KeyName::Numpad(1),
+            KeyCode::NumPad2 => KeyName::Numpad(2),
+            KeyCode::NumPad3 => KeyName::Numpad(3),
+            KeyCode::NumPad4 => KeyName::Numpad(4),
+            KeyCode::NumPad5 => KeyName::Numpad(5),
+            KeyCode::NumPad6 => KeyName::Numpad(6),
+            KeyCode::NumPad7 => KeyName::Numpad(7),
+            KeyCode::NumPad8 => KeyName::Numpad(8),
+            KeyCode::NumPad9 => KeyName::Numpad(9),
+            _ => KeyName::Other,
+        };
+        KeyPress {
+            name,
+            ctrl: key.ctrl,
+            alt: key.alt,
+            shift: key.shift,
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::VecDeque;
+use std::io::{self, Stdout, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 3party
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers, MouseButton, MouseEventKind,
+};
+use crossterm::style::{self, Colors, Print, SetColors};
+use crossterm::{cursor, queue, terminal};
+use tcod::Color;
+
+use super::grid::{Cell, GridRenderer};
+use super::sdl::LIMIT_FPS;
+use super::{InputEvent, Renderer};
+use crate::frontend::input::{KeyName, KeyPress, MouseState};
+
+// frames are spaced like in the tcod window, so polling loops don't spin
+const FRAME_TIME: Duration = Duration::from_millis(1000 / LIMIT_FPS as u64);
+
+// A plain ANSI terminal, e.g. over SSH. Only the cells that changed since the last
+// flush are sent.
+pub struct TerminalRenderer {
+    out: Stdout,
+    // what the next flush shows
+    grid: GridRenderer,
+    // what the terminal shows, None where it is unknown
+    shown: Vec<Option<Cell>>,
+    // a typed character is reported as the key and as its text, like tcod does
+    pending: VecDeque<KeyPress>,
+    last_flush: Instant,
+    // the terminal went away, e.g. a dropped SSH connection
+    closed: bool,
+}
+
+impl TerminalRenderer {
+    pub fn new() -> io::Result<Self> {
+        let mut out = io::stdout();
+        terminal::enable_raw_mode()?;
+        queue!(
+            out,
+            terminal::EnterAlternateScreen,
+            cursor::Hide,
+            EnableMouseCapture
+        )?;
+        out.flush()?;
+        let (width, height) = terminal::size()?;
+        let mut renderer = TerminalRenderer {
+            out,
+            grid: GridRenderer::new(0, 0),
+            shown: vec![],
+            pending: VecDeque::new(),
+            last_flush: Instant::now(),
+            closed: false,
+        };
+        renderer.resize(width as i32, height as i32);
+        Ok(renderer)
+    }
+
+    fn resize(&mut self, width: i32, height: i32) {
+        self.grid = GridRenderer::new(width, height);
+        self.shown = vec![None; (width * height) as usize];
+    }
+
+    // The event waiting to be read, if any. Failing to read means the terminal is gone.
+    fn poll_event(&mut self) -> Option<Event> {
+        let event = match event::poll(Duration::ZERO) {
+            Ok(true) => event::read().map(Some),
+            Ok(false) => Ok(None),
+            Err(e) => Err(e),
+        };
+        event.unwrap_or_else(|_| {
+            self.closed = true;
+            None
+        })
+    }
+
+    // Keys go to `pending`, mouse events are returned.
+    fn handle_event(&mut self, event: Event) -> Option<InputEvent> {
+        match event {
+            Event::Key(key) if key.kind != KeyEventKind::Release => {
+                self.pending.extend(key_presses(key));
+                None
+            }
+            Event::Mouse(mouse) => {
+                let (x, y) = (mouse.column as i32, mouse.row as i32);
+                let pressed = match mouse.kind {
+                    MouseEventKind::Down(button) => Some(button),
+                    _ => None,
+                };
+                Some(InputEvent::Mouse(MouseState {
+                    x,
+                    y,
+                    lbutton_pressed: pressed == Some(MouseButton::Left),
+                    rbutton_pressed: pressed == Some(MouseButton::Right),
+                }))
+            }
+            Event::Resize(width, height) => {
+                self.resize(width as i32, height as i32);
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Drop for TerminalRenderer {
+    fn drop(&mut self) {
+        let _ = queue!(
+            self.out,
+            DisableMouseCapture,
+            style::ResetColor,
+            cursor::Show,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = self.out.flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+impl Renderer for TerminalRenderer {
+    fn size(&self) -> (i32, i32) {
+        self.grid.size()
+    }
+    fn clear(&mut self) {
+        self.grid.clear();
+    }
+    fn put_char(&mut self, x: i32, y: i32, c: char, color: Color) {
+        self.grid.put_char(x, y, c, color);
+    }
+    fn set_background(&mut self, x: i32, y: i32, color: Color) {
+        self.grid.set_background(x, y, color);
+    }
+    fn flush(&mut self) {
+        let (width, _) = self.grid.size();
+        for (index, &cell) in self.grid.cells().iter().enumerate() {
+            if self.shown[index] == Some(cell) {
+                continue;
+            }
+            let (x, y) = (index as i32 % width, index as i32 / width);
+            let _ = queue!(
+                self.out,
+                cursor::MoveTo(x as u16, y as u16),
+                SetColors(Colors::new(rgb(cell.fg), rgb(cell.bg))),
+                Print(cell.c)
+            );
+            self.shown[index] = Some(cell);
+        }
+        let _ = self.out.flush();
+
+        let elapsed = self.last_flush.elapsed();
+        if elapsed < FRAME_TIME {
+            thread::sleep(FRAME_TIME - elapsed);
+        }
+        self.last_flush = Instant::now();
+    }
+    fn wait_for_key(&mut self) -> KeyPress {
+        loop {
+            if let Some(key) = self.pending.pop_front() {
+                return key;
+            }
+            match event::read() {
+                Ok(event) => {
+                    self.handle_event(event);
+                }
+                // the terminal is gone, there is nobody left to play
+                Err(_) => {
+                    self.closed = true;
+                    return escape();
+                }
+            }
+        }
+    }
+    fn check_for_event(&mut self) -> Option<InputEvent> {
+        if self.pending.is_empty() {
+            if let Some(mouse) = self.poll_event().and_then(|event| self.handle_event(event)) {
+                return Some(mouse);
+            }
+        }
+        self.pending.pop_front().map(InputEvent::Key)
+    }
+    fn window_closed(&self) -> bool {
+        self.closed
+    }
+}
+
+fn rgb(color: Color) -> style::Color {
+    style::Color::Rgb {
+        r: color.r,
+        g: color.g,
+        b: color.b,
+    }
+}
+
+fn escape() -> KeyPress {
+    KeyPress {
+        name: KeyName::Escape,
+        ctrl: false,
+        alt: false,
+        shift: false,
+    }
+}
+
+fn key_presses(key: KeyEvent) -> Vec<KeyPress> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let alt = key.modifiers.contains(KeyModifiers::ALT);
+    let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+    let press = |name| KeyPress {
+        name,
+        ctrl,
+        alt,
+        shift,
+    };
+    let name = match key.code {
+        KeyCode::Char(c) => {
+            let mut presses = vec![press(KeyName::Char(c.to_ascii_lowercase()))];
+            if !ctrl && !alt {
+                presses.push(press(KeyName::Text(c)));
+            }
+            return presses;
+        }
+        KeyCode::Up => KeyName::Up,
+        KeyCode::Down => KeyName::Down,
+        KeyCode::Left => KeyName::Left,
+        KeyCode::Right => KeyName::Right,
+        KeyCode::Enter => KeyName::Enter,
+        KeyCode::Esc => KeyName::Escape,
+        KeyCode::Backspace => KeyName::Backspace,
+        KeyCode::PageUp => KeyName::PageUp,
+        KeyCode::PageDown => KeyName::PageDown,
+        _ => KeyName::Other,
+    };
+    vec![press(name)]
+}
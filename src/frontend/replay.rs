@@ -0,0 +1,172 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fs;
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::engine::mapgen::MapGenerator;
+
+use super::input::KeyName;
+use super::keymap::Command;
+use super::render::Frontend;
+use super::renderer::InputEvent;
+
+pub const REPLAY_FILE: &str = "last_game.replay";
+pub const REPLAY_VERSION: u32 = 6;
+pub const MAX_REPLAY_DELAY: Duration = Duration::from_secs(2);
+
+// Everything the player decided during a run. Together with the seed and the map
+// generator it is enough to play the run again. Commands are kept rather than keys, so
+// a recording plays the same under any keymap.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RecordedInput {
+    Command(Option<Command>),
+    Menu(Option<usize>),
+    Target(Option<(i32, i32)>),
+}
+
+impl RecordedInput {
+    pub fn command(&self) -> Option<Option<Command>> {
+        match *self {
+            RecordedInput::Command(command) => Some(command),
+            _ => None,
+        }
+    }
+    pub fn menu(&self) -> Option<Option<usize>> {
+        match *self {
+            RecordedInput::Menu(choice) => Some(choice),
+            _ => None,
+        }
+    }
+    pub fn target(&self) -> Option<Option<(i32, i32)>> {
+        match *self {
+            RecordedInput::Target(target) => Some(target),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Recording {
+    pub version: u32,
+    pub seed: u64,
+    pub generator: MapGenerator,
+    pub inputs: Vec<RecordedInput>,
+}
+
+impl Recording {
+    pub fn new(seed: u64, generator: MapGenerator) -> Self {
+        Recording {
+            version: REPLAY_VERSION,
+            seed,
+            generator,
+            inputs: vec![],
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+        let recording = serde_json::from_str::<Recording>(&fs::read_to_string(path)?)?;
+        if recording.version != REPLAY_VERSION {
+            return Err(format!(
+                "replay version {} is not supported (expected {})",
+                recording.version, REPLAY_VERSION
+            )
+            .into());
+        }
+        Ok(recording)
+    }
+}
+
+// Feeds a recording back in place of the player. While it runs, '+' and '-' change the
+// speed, space toggles step-by-step mode and Esc hands control back to the player.
+pub struct Replay {
+    inputs: VecDeque<RecordedInput>,
+    pub delay: Duration,
+    pub step: bool,
+}
+
+impl Replay {
+    pub fn new(recording: Recording, delay: Duration, step: bool) -> Self {
+        Replay {
+            inputs: recording.inputs.into(),
+            delay,
+            step,
+        }
+    }
+
+    // Wait until the next input is due. Returns false when the player stopped the replay.
+    fn wait(&mut self, frontend: &mut Frontend) -> bool {
+        use KeyName::*;
+        loop {
+            let key = if self.step {
+                Some(frontend.screen.wait_for_key())
+            } else {
+                thread::sleep(self.delay);
+                match frontend.screen.check_for_event() {
+                    Some(InputEvent::Key(key)) => Some(key),
+                    _ => None,
+                }
+            };
+            match key.map(|key| key.name) {
+                Some(Escape) => return false,
+                Some(Char(' ')) => self.step = !self.step,
+                Some(Char('+' | '=')) => {
+                    self.delay = (self.delay / 2).max(Duration::from_millis(1));
+                }
+                Some(Char('-')) => {
+                    self.delay = (self.delay * 2).clamp(Duration::from_millis(1), MAX_REPLAY_DELAY);
+                }
+                // the text a key typed follows the key itself, it is no key press of its own
+                Some(Text(_)) => {}
+                // in step mode any other key advances by one input
+                _ => return true,
+            }
+            if frontend.screen.window_closed() {
+                return false;
+            }
+        }
+    }
+}
+
+// Next player decision: taken from the replay while one runs, asked from the player
+// otherwise. Either way it is added to the recording, if there is one.
+pub fn next_input<T: Copy>(
+    frontend: &mut Frontend,
+    from_recorded: fn(&RecordedInput) -> Option<T>,
+    ask_player: impl FnOnce(&mut Frontend) -> T,
+    to_recorded: fn(T) -> RecordedInput,
+) -> T {
+    let replayed = replayed_input(frontend).and_then(|input| {
+        let value = from_recorded(&input);
+        if value.is_none() {
+            // the run went somewhere the recording didn't, stop trusting it
+            frontend.replay = None;
+        }
+        value
+    });
+    let value = match replayed {
+        Some(value) => value,
+        None => ask_player(frontend),
+    };
+    if let Some(recording) = frontend.recording.as_mut() {
+        recording.inputs.push(to_recorded(value));
+    }
+    value
+}
+
+fn replayed_input(frontend: &mut Frontend) -> Option<RecordedInput> {
+    let mut replay = frontend.replay.take()?;
+    if replay.inputs.is_empty() || !replay.wait(frontend) {
+        return None;
+    }
+    let input = replay.inputs.pop_front();
+    frontend.replay = Some(replay);
+    input
+}
@@ -0,0 +1,200 @@
+use tcod::colors::WHITE;
+
+use crate::engine::entity::*;
+use crate::engine::object::*;
+use crate::engine::Game;
+
+use super::input::KeyName;
+use super::keymap::KEYMAP_FILE;
+use super::render::{wrap_text, Frontend};
+use super::renderer::grid::GridRenderer;
+use super::renderer::Renderer;
+use super::replay::{next_input, RecordedInput};
+
+pub const INVENTORY_WIDTH: i32 = 50;
+pub const LEVEL_SCREEN_WIDTH: i32 = 40;
+pub const HELP_SCREEN_WIDTH: i32 = 50;
+
+pub fn menu<T>(header: &str, options: &[T], width: i32, screen: &mut dyn Renderer) -> Option<usize>
+where
+    T: AsRef<str>,
+{
+    assert!(
+        options.len() <= 26,
+        "Cannot have a menu with more than 26 options."
+    );
+    let header_lines = if header.is_empty() {
+        vec![]
+    } else {
+        wrap_text(header, width)
+    };
+    let header_height = header_lines.len() as i32;
+    let height = header_height + options.len() as i32;
+
+    let mut window = GridRenderer::new(width, height);
+    for (row, line) in header_lines.iter().enumerate() {
+        window.print(0, row as i32, line, WHITE);
+    }
+    for (index, option_text) in options.iter().enumerate() {
+        let menu_letter = (b'a' + index as u8) as char;
+        let text = format!("({}) {}", menu_letter, option_text.as_ref());
+        window.print(0, header_height + index as i32, &text, WHITE);
+    }
+    let (screen_width, screen_height) = screen.size();
+    let x = screen_width / 2 - width / 2;
+    let y = screen_height / 2 - height / 2;
+    screen.blit(&window, x, y);
+
+    screen.flush();
+    match screen.wait_for_key().name {
+        KeyName::Char(c) if c.is_ascii_lowercase() => {
+            let index = c as usize - 'a' as usize;
+            if index < options.len() {
+                Some(index)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+// A menu whose answer is part of the game's recorded input.
+pub fn recorded_menu<T>(
+    header: &str,
+    options: &[T],
+    width: i32,
+    frontend: &mut Frontend,
+) -> Option<usize>
+where
+    T: AsRef<str>,
+{
+    next_input(
+        frontend,
+        RecordedInput::menu,
+        |frontend| menu(header, options, width, frontend.screen.as_mut()),
+        RecordedInput::Menu,
+    )
+}
+
+pub fn msgbox(text: &str, width: i32, screen: &mut dyn Renderer) {
+    let options: &[&str] = &[];
+    menu(text, options, width, screen);
+}
+
+pub fn inventory_menu(
+    inventory: &[Entity],
+    objects: &Objects,
+    header: &str,
+    frontend: &mut Frontend,
+) -> Option<usize> {
+    let options = if inventory.is_empty() {
+        vec!["Inventory is empty.".into()]
+    } else {
+        inventory
+            .iter()
+            .map(|&item| match objects.equipment.get(item) {
+                Some(equipment) if equipment.equipped => {
+                    format!("{} (on {})", objects.name(item), equipment.slot)
+                }
+                _ => objects.name(item).to_string(),
+            })
+            .collect()
+    };
+
+    let inventory_index = recorded_menu(header, &options, INVENTORY_WIDTH, frontend);
+    if !inventory.is_empty() {
+        inventory_index
+    } else {
+        None
+    }
+}
+
+pub fn help_screen(frontend: &mut Frontend) {
+    let mut text = format!("Keys, rebound in {}:\n\n", KEYMAP_FILE);
+    for (command, keys) in frontend.keymap.iter() {
+        let keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+        text += &format!("{:<16} {}\n", keys.join(", "), command.description());
+    }
+    msgbox(&text, HELP_SCREEN_WIDTH, frontend.screen.as_mut());
+}
+
+pub fn level_up_menu(frontend: &mut Frontend, game: &mut Game, objects: &mut Objects) {
+    if !can_level_up(objects) {
+        return;
+    }
+
+    let fighter = objects.fighters[PLAYER];
+    let mut choice = None;
+    while choice.is_none() {
+        // nobody is left to choose, the level up waits in the save
+        if frontend.screen.window_closed() {
+            return;
+        }
+        choice = recorded_menu(
+            "Level up! Choose a stat to raise:\n",
+            &[
+                format!("Constitution (+20 HP, from {})", fighter.base_max_hp),
+                format!("Strength (+1 attack, from {})", fighter.base_power),
+                format!("Agility (+1 defense, from {})", fighter.base_defense),
+            ],
+            LEVEL_SCREEN_WIDTH,
+            frontend,
+        );
+    }
+    let stat = match choice.unwrap() {
+        0 => Stat::Constitution,
+        1 => Stat::Strength,
+        _ => Stat::Agility,
+    };
+    level_up(stat, game, objects);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frontend::input::KeyPress;
+
+    fn key(c: char) -> KeyPress {
+        KeyPress {
+            name: KeyName::Char(c),
+            ctrl: false,
+            alt: false,
+            shift: false,
+        }
+    }
+
+    #[test]
+    fn menu_is_centered_over_the_screen() {
+        let mut screen = GridRenderer::new(24, 7);
+        for y in 0..7 {
+            screen.print(0, y, &"#".repeat(24), WHITE);
+        }
+        screen.press(key('b'));
+        let choice = menu("Pick a potion:", &["healing", "confusion"], 16, &mut screen);
+        assert_eq!(choice, Some(1));
+        assert_eq!(
+            screen.to_ascii(),
+            [
+                "########################",
+                "########################",
+                "####Pick a potion:  ####",
+                "####(a) healing     ####",
+                "####(b) confusion   ####",
+                "########################",
+                "########################",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn menu_wraps_a_long_header() {
+        let mut screen = GridRenderer::new(12, 4);
+        screen.press(key('z'));
+        let options: &[&str] = &[];
+        let choice = menu("Nothing to see here.", options, 10, &mut screen);
+        assert_eq!(choice, None);
+        assert_eq!(screen.to_ascii(), "\n Nothing to\n see here.\n");
+    }
+}
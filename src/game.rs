@@ -1,26 +0,0 @@
-pub mod input;
-pub mod map;
-pub mod object;
-pub mod render;
-pub mod ui;
-
-use map::Map;
-
-use self::{render::Messages, object::Object};
-
-pub struct Game {
-    pub map: Map,
-    pub messages: Messages,
-    pub inventory: Vec<Object>,
-}
-
-pub fn mut_two<T>(first_index: usize, second_index: usize, items: &mut [T]) -> (&mut T, &mut T) {
-    assert!(first_index != second_index);
-    let split_at_index = std::cmp::max(first_index, second_index);
-    let (first_slice, second_slice) = items.split_at_mut(split_at_index);
-    if first_index < second_index {
-        (&mut first_slice[first_index], &mut second_slice[0])
-    } else {
-        (&mut second_slice[0], &mut first_slice[second_index])
-    }
-}
@@ -0,0 +1,4 @@
+// The engine holds the rules of the game and never touches a console, the frontend
+// draws it and turns player input into engine calls.
+pub mod engine;
+pub mod frontend;
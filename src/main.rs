@@ -1,115 +1,283 @@
+use std::rc::Rc;
+use std::time::Duration;
+
 // 3party
 use tcod::colors::*;
-use tcod::console::*;
-use tcod::map::Map as FovMap;
 
 // own module
-mod game;
+use roguelike::engine::content::Content;
+use roguelike::engine::entity::*;
+use roguelike::engine::mapgen::MapGenerator;
+use roguelike::engine::object::Objects;
+use roguelike::engine::save::*;
+use roguelike::engine::schedule::run_monster_turns;
+use roguelike::engine::*;
+use roguelike::frontend::input::*;
+use roguelike::frontend::keymap::*;
+use roguelike::frontend::render::*;
+use roguelike::frontend::renderer::sdl::TcodRenderer;
+use roguelike::frontend::renderer::terminal::TerminalRenderer;
+use roguelike::frontend::renderer::Renderer;
+use roguelike::frontend::replay::*;
+use roguelike::frontend::ui::*;
+use roguelike::frontend::{SCREEN_HEIGHT, SCREEN_WIDTH};
 
-use game::input::*;
-use game::map::*;
-use game::object::*;
-use game::render::*;
-use game::*;
+const DEFAULT_REPLAY_DELAY_MS: u64 = 100;
 
-const SCREEN_WIDTH: i32 = 160;
-const SCREEN_HEIGHT: i32 = 100;
+fn main() {
+    // bad data files are a designer mistake, report them before opening the window
+    let content = match Content::load() {
+        Ok(content) => Rc::new(content),
+        Err(e) => {
+            eprintln!("Cannot load the game data: {}", e);
+            std::process::exit(1);
+        }
+    };
 
-const LIMIT_FPS: i32 = 20;
+    let keymap = match Keymap::load(KEYMAP_FILE) {
+        Ok(keymap) => keymap,
+        Err(e) => {
+            eprintln!("Cannot load the keymap: {}", e);
+            std::process::exit(1);
+        }
+    };
 
-fn main() {
-    // console settings
-    let root = Root::initializer()
-        .font("arial10x10.png", FontLayout::Tcod)
-        .font_type(FontType::Greyscale)
-        .size(SCREEN_WIDTH, SCREEN_HEIGHT)
-        .title("Roguelike!")
-        .init();
-    let mut tcod = Tcod {
-        root,
-        con: Offscreen::new(MAP_WIDTH, MAP_HEIGHT),
-        panel: Offscreen::new(SCREEN_WIDTH, PANEL_HEIGHT),
-        fov: FovMap::new(MAP_WIDTH, MAP_HEIGHT),
-        key: Default::default(),
+    // `--terminal` plays in the terminal instead of the tcod window, e.g. over SSH
+    let screen: Box<dyn Renderer> = if std::env::args().any(|arg| arg == "--terminal") {
+        match TerminalRenderer::new() {
+            Ok(terminal) => Box::new(terminal),
+            Err(e) => {
+                eprintln!("Cannot set up the terminal: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        Box::new(TcodRenderer::new(SCREEN_WIDTH, SCREEN_HEIGHT))
+    };
+    let mut frontend = Frontend {
+        screen,
         mouse: Default::default(),
+        recording: None,
+        replay: None,
+        keymap,
     };
-    tcod::system::set_fps(LIMIT_FPS);
-
-    'start_game: loop {
-        // objects and maps settings
-        let mut player = Object::new(0, 0, '@', "Cuppar", WHITE, true);
-        player.fighter = Some(Fighter {
-            max_hp: 30,
-            hp: 30,
-            defense: 2,
-            power: 5,
-            on_death: DeathCallback::Player,
-        });
-        player.alive = true;
-        let mut objects = vec![player];
-        let mut game = Game {
-            map: make_map(&mut objects),
-            messages: Messages::new(),
-            inventory: Default::default(),
-        };
-
-        // set fov map
-        for y in 0..MAP_HEIGHT {
-            for x in 0..MAP_WIDTH {
-                tcod.fov.set(
-                    x,
-                    y,
-                    !game.map[x as usize][y as usize].block_sight,
-                    // !game.map[x as usize][y as usize].blocked,
-                    false,
-                )
-            }
+
+    let seed = parse_seed();
+    let generator = parse_generator();
+    let history = parse_history();
+    if let Some(path) = arg_value("--replay") {
+        replay_game(&mut frontend, &path, &content, history);
+    }
+    main_menu(&mut frontend, seed, generator, history, &content);
+}
+
+fn arg_value(name: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == name)?;
+    args.get(index + 1).cloned()
+}
+
+// `--seed <n>` makes every new game of this session start from the same dungeon
+fn parse_seed() -> Option<u64> {
+    let value = arg_value("--seed");
+    match value.as_ref().map(|value| value.parse()) {
+        Some(Ok(seed)) => Some(seed),
+        Some(Err(_)) => {
+            eprintln!("--seed expects an unsigned integer, using a random seed instead");
+            None
         }
+        None => None,
+    }
+}
 
-        let mut previous_player_position = (-1, -1);
-        game.messages.add("Welcome!", RED);
+// `--map <rooms|bsp|cave>` picks the algorithm the levels of new games are carved with
+fn parse_generator() -> MapGenerator {
+    let value = arg_value("--map");
+    match value.as_deref().map(MapGenerator::from_name) {
+        Some(Some(generator)) => generator,
+        Some(None) => {
+            eprintln!("--map expects rooms, bsp or cave, using rooms instead");
+            MapGenerator::default()
+        }
+        None => MapGenerator::default(),
+    }
+}
 
-        // main loop
-        while !tcod.root.window_closed() {
-            // This function has bug?
-            // match input::check_for_event(input::MOUSE | input::KEY_PRESS) {
-            //     Some((_, Event::Mouse(m))) => tcod.mouse = m,
-            //     Some((_, Event::Key(k))) => tcod.key = k,
-            //     _ => tcod.key = Default::default(),
-            // }
+// `--history <n>` keeps the last n messages for the message log
+fn parse_history() -> usize {
+    let value = arg_value("--history");
+    match value.as_ref().map(|value| value.parse()) {
+        Some(Ok(history)) if history > 0 => history,
+        Some(_) => {
+            eprintln!(
+                "--history expects a positive integer, keeping {} messages instead",
+                MESSAGE_HISTORY_SIZE
+            );
+            MESSAGE_HISTORY_SIZE
+        }
+        None => MESSAGE_HISTORY_SIZE,
+    }
+}
 
-            // clear prev frame
-            tcod.con.clear();
+// `--replay <file>` plays a recorded game back, `--replay-delay <ms>` sets the pause
+// between inputs and `--step` waits for a key press before each of them.
+fn replay_game(frontend: &mut Frontend, path: &str, content: &Rc<Content>, history: usize) {
+    let recording = match Recording::load(path) {
+        Ok(recording) => recording,
+        Err(e) => {
+            msgbox(
+                &format!("\nCannot load the replay {}: {}\n", path, e),
+                50,
+                frontend.screen.as_mut(),
+            );
+            return;
+        }
+    };
+    let delay_ms = arg_value("--replay-delay")
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_REPLAY_DELAY_MS);
+    let step = std::env::args().any(|arg| arg == "--step");
 
-            // render
-            let fov_recompute = previous_player_position != objects[PLAYER].pos();
-            render_all(&mut tcod, &mut game, &objects, fov_recompute);
-            tcod.root.flush();
+    let (game, objects) = new_game(recording.seed, recording.generator, content.clone());
+    frontend.replay = Some(Replay::new(
+        recording,
+        Duration::from_millis(delay_ms),
+        step,
+    ));
+    play_game(frontend, game, objects, None, history);
+    frontend.replay = None;
+}
 
-            previous_player_position = objects[PLAYER].pos();
+fn main_menu(
+    frontend: &mut Frontend,
+    seed: Option<u64>,
+    generator: MapGenerator,
+    history: usize,
+    content: &Rc<Content>,
+) {
+    while !frontend.screen.window_closed() {
+        let screen = frontend.screen.as_mut();
+        screen.clear();
+        let (width, height) = screen.size();
+        let title = "Roguelike!";
+        screen.print(
+            width / 2 - title.len() as i32 / 2,
+            height / 2 - 6,
+            title,
+            LIGHT_YELLOW,
+        );
 
-            // handle user input
-            let player_action = handle_keys(&mut tcod, &mut game, &mut objects);
-            if player_action == PlayerAction::Exit {
-                break 'start_game;
+        let choices = &["Play a new game", "Continue last game", "Quit"];
+        let choice = menu("", choices, 24, frontend.screen.as_mut());
+        match choice {
+            Some(0) => {
+                let (game, objects) = new_game(
+                    seed.unwrap_or_else(rand::random),
+                    generator,
+                    content.clone(),
+                );
+                frontend.recording = Some(Recording::new(game.seed, game.generator));
+                play_game(frontend, game, objects, seed, history);
             }
-
-            if objects[PLAYER].alive && player_action == PlayerAction::TookTurn {
-                for id in 0..objects.len() {
-                    // monster turn
-                    if objects[id].ai.is_some() {
-                        ai_take_turn(id, &tcod, &mut game, &mut objects);
-                    }
+            Some(1) => match load_game(content.clone()) {
+                Ok((game, objects)) => {
+                    play_game(frontend, game, objects, seed, history);
                 }
-            }
-            if !objects[PLAYER].alive {
-                // user die
-                if player_action == PlayerAction::Replay {
-                    break;
-                } else {
-                    game.messages.add("replay? (y/esc)".to_string(), RED);
+                Err(e) => {
+                    msgbox(
+                        &format!("\nCannot load the saved game: {}\n", e),
+                        50,
+                        frontend.screen.as_mut(),
+                    );
                 }
+            },
+            Some(2) => break,
+            _ => {}
+        }
+    }
+}
+
+fn play_game(
+    frontend: &mut Frontend,
+    mut game: Game,
+    mut objects: Objects,
+    seed: Option<u64>,
+    history: usize,
+) {
+    // a replay must not overwrite the save of the player's own game
+    let is_replay = frontend.replay.is_some();
+    game.messages.set_capacity(history);
+
+    // main loop
+    while !frontend.screen.window_closed() {
+        // This function has bug?
+        // match input::check_for_event(input::MOUSE | input::KEY_PRESS) {
+        //     Some((_, Event::Mouse(m))) => tcod.mouse = m,
+        //     Some((_, Event::Key(k))) => tcod.key = k,
+        //     _ => tcod.key = Default::default(),
+        // }
+
+        // render
+        render_all(frontend, &game, &objects);
+        frontend.screen.flush();
+
+        // handle user input
+        let player_action = handle_keys(frontend, &mut game, &mut objects);
+        if player_action == PlayerAction::Exit {
+            break;
+        }
+
+        if objects.is_alive(PLAYER) {
+            level_up_menu(frontend, &mut game, &mut objects);
+        }
+
+        if let PlayerAction::TookTurn(action) = player_action {
+            if objects.is_alive(PLAYER) {
+                run_monster_turns(action, &mut game, &mut objects);
+            }
+        }
+        if !objects.is_alive(PLAYER) {
+            // user die
+            if player_action == PlayerAction::Replay {
+                finish_recording(frontend);
+                let content = game.content.clone();
+                (game, objects) =
+                    new_game(seed.unwrap_or_else(rand::random), game.generator, content);
+                game.messages.set_capacity(history);
+                frontend.recording = Some(Recording::new(game.seed, game.generator));
+            } else {
+                game.messages.add("replay? (y/esc)".to_string(), RED);
             }
         }
     }
+
+    finish_recording(frontend);
+    if is_replay {
+        return;
+    }
+
+    // save on exit, a finished run has nothing left to continue
+    if objects.is_alive(PLAYER) {
+        if let Err(e) = save_game(&game, &objects) {
+            msgbox(
+                &format!("\nCannot save the game: {}\n", e),
+                50,
+                frontend.screen.as_mut(),
+            );
+        }
+    } else {
+        delete_save();
+    }
+}
+
+fn finish_recording(frontend: &mut Frontend) {
+    if let Some(recording) = frontend.recording.take() {
+        if let Err(e) = recording.save(REPLAY_FILE) {
+            msgbox(
+                &format!("\nCannot save the replay: {}\n", e),
+                50,
+                frontend.screen.as_mut(),
+            );
+        }
+    }
 }